@@ -1,5 +1,6 @@
 // cli.rs - parse CLI arguments
 use clap::Parser;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -10,4 +11,13 @@ pub struct CliArgs {
     /// Perform various statistics calculations / tests
     #[clap(short = 't', long, default_value_t = false)]
     pub stats: bool,
+    /// Read config files from this directory first (also `RELICS_CONFIG_DIR`)
+    #[clap(long)]
+    pub config_dir: Option<PathBuf>,
+    /// Read data files from this directory first (also `RELICS_DATA_DIR`)
+    #[clap(long)]
+    pub data_dir: Option<PathBuf>,
+    /// Reload config and data files when they change, keeping the old ones if the new ones are invalid
+    #[clap(short, long, default_value_t = false)]
+    pub watch: bool,
 }
@@ -3,10 +3,7 @@ use bevy_ecs::prelude::*;
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    gamelogic::{RollResult, RollResultType, Rollable},
-    rng,
-};
+use crate::{gamelogic::Rollable, map::DoorAction};
 
 //----------------//
 // Helper structs //
@@ -126,55 +123,23 @@ pub struct Attribute {
     pub bonus: i32,
 }
 
-impl Rollable for Attribute {
-    fn check(&self, roll: i32) -> RollResult {
-        let great_success = (self.base as f32 * 0.4).max(3.0) as i32;
-        let extreme_success = (self.base as f32 * 0.1).max(2.0) as i32;
-
-        let mut great_failure = ((100 - self.base) as f32 * 0.4).max(2.0) as i32;
-        let mut extreme_failure = ((100 - self.base) as f32 * 0.1).max(1.0) as i32;
-
-        if great_failure < 2 {
-            great_failure = 2;
-        }
-        if extreme_failure < 1 {
-            extreme_failure = 1;
-        }
-
-        println!("base is {}, roll is {}", self.base, roll);
-        println!("threshold for great success is {}", great_success);
-        println!("threshold for extreme success is {}", extreme_success);
-        println!("threshold for great failure is {}", 100 - great_failure);
-        println!("threshold for extreme failure is {}", 100 - extreme_failure);
-
-        if roll <= self.base {
-            if roll == 1 {
-                RollResult::Success(RollResultType::Critical)
-            } else if roll <= extreme_success {
-                RollResult::Success(RollResultType::Extreme)
-            } else if roll <= great_success {
-                RollResult::Success(RollResultType::Great)
-            } else {
-                RollResult::Success(RollResultType::Normal)
-            }
-        } else if roll > self.base {
-            if roll == 100 {
-                RollResult::Failure(RollResultType::Critical)
-            } else if (100 - roll) < extreme_failure {
-                RollResult::Failure(RollResultType::Extreme)
-            } else if (100 - roll) < great_failure {
-                RollResult::Failure(RollResultType::Great)
-            } else {
-                RollResult::Failure(RollResultType::Normal)
-            }
-        } else {
-            RollResult::Failure(RollResultType::Normal)
-        }
+impl Attribute {
+    /// The effective value of the attribute, including modifiers and bonuses.
+    pub fn value(&self) -> i32 {
+        self.base + self.modifiers + self.bonus
     }
+}
+
+/// How often (in game ticks) and by how much a `Pool` regenerates.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Regen {
+    pub frequency: i32,
+    pub amount: i32,
+}
 
-    fn roll(&self) -> RollResult {
-        let roll = rng::roll_str("1d100");
-        self.check(roll)
+impl Rollable for Attribute {
+    fn target(&self) -> i32 {
+        self.value()
     }
 }
 
@@ -207,6 +172,10 @@ pub struct Mental;
 #[derive(Component, Default, Serialize, Deserialize, Debug)]
 pub struct Spiritual;
 
+/// Indicates that an entity is currently fighting.
+#[derive(Component, Default, Serialize, Deserialize, Debug)]
+pub struct InCombat;
+
 //----------------------//
 // Components with data //
 //----------------------//
@@ -235,7 +204,7 @@ pub struct DetailedName {
 
 /// Stats component. Holds all stats an entity can have.
 /// These stats change more or less frequently.
-#[derive(Component, Default, Serialize, Deserialize)]
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
 pub struct Stats {
     /// Hitpoints
     pub hp: Pool,
@@ -251,6 +220,60 @@ pub struct Stats {
     pub lv: i32,
 }
 
+/// Hit point and magic point regeneration.
+/// The frequencies are shortened by the entity's CON (for hit points) and INT (for magic points).
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Regeneration {
+    pub hp: Regen,
+    pub mp: Regen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Skill {
+    Melee,
+    Defense,
+    Magic,
+}
+
+/// Skill levels, and skill points which have not been spent yet.
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Skills {
+    pub melee: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub unspent: i32,
+}
+
+impl Skills {
+    pub fn get(&self, skill: Skill) -> i32 {
+        match skill {
+            Skill::Melee => self.melee,
+            Skill::Defense => self.defense,
+            Skill::Magic => self.magic,
+        }
+    }
+
+    /// Spends one unspent skill point on `skill`. Returns false if there were no points to spend.
+    pub fn allocate(&mut self, skill: Skill) -> bool {
+        if self.unspent <= 0 {
+            return false;
+        }
+        self.unspent -= 1;
+        match skill {
+            Skill::Melee => self.melee += 1,
+            Skill::Defense => self.defense += 1,
+            Skill::Magic => self.magic += 1,
+        }
+        true
+    }
+}
+
+/// Names of the spells an entity is able to cast.
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KnownSpells {
+    pub spells: Vec<String>,
+}
+
 #[derive(Component, Default, Serialize, Deserialize)]
 pub struct Attributes {
     pub str: Attribute,
@@ -265,8 +288,61 @@ pub struct Attributes {
 #[derive(Component, Debug, Serialize, Deserialize)]
 pub enum Intent {
     Nothing,
-    MoveRelative { dx: i32, dy: i32 },
-    MoveAbsolute { x: i32, y: i32 },
+    MoveRelative {
+        dx: i32,
+        dy: i32,
+    },
+    MoveAbsolute {
+        x: i32,
+        y: i32,
+    },
+    // Entity ids aren't stable between runs, so these can't be saved.
+    #[serde(skip)]
+    Throw {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    Fire {
+        weapon: Entity,
+        ammo: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    UseItem {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    Equip {
+        item: Entity,
+    },
+    #[serde(skip)]
+    Unequip {
+        item: Entity,
+    },
+    #[serde(skip)]
+    Drop {
+        item: Entity,
+    },
+    PickUp,
+    OperateDoor {
+        action: DoorAction,
+        x: i32,
+        y: i32,
+    },
+    Bash {
+        x: i32,
+        y: i32,
+    },
+    Cast {
+        spell: usize,
+        x: i32,
+        y: i32,
+    },
 }
 
 impl Intent {
@@ -275,6 +351,16 @@ impl Intent {
             Self::Nothing => 100,
             Self::MoveAbsolute { .. } => 200,
             Self::MoveRelative { .. } => 100,
+            Self::Throw { .. } => 100,
+            Self::Fire { .. } => 100,
+            Self::UseItem { .. } => 100,
+            Self::Equip { .. } => 100,
+            Self::Unequip { .. } => 100,
+            Self::Drop { .. } => 100,
+            Self::PickUp => 100,
+            Self::OperateDoor { .. } => 100,
+            Self::Bash { .. } => 100,
+            Self::Cast { .. } => 100,
         }
     }
 }
@@ -283,8 +369,65 @@ impl Intent {
 /// It has already been determined that the entity is able to.
 #[derive(Component, Debug, Serialize, Deserialize)]
 pub enum PerformAction {
-    MoveAbsolute { x: i32, y: i32 },
-    MoveRelative { dx: i32, dy: i32 },
+    MoveAbsolute {
+        x: i32,
+        y: i32,
+    },
+    MoveRelative {
+        dx: i32,
+        dy: i32,
+    },
+    // Entity ids aren't stable between runs, so this can't be saved.
+    #[serde(skip)]
+    MeleeAttack {
+        target: Entity,
+    },
+    #[serde(skip)]
+    Throw {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    Fire {
+        weapon: Entity,
+        ammo: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    UseItem {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    Equip {
+        item: Entity,
+    },
+    #[serde(skip)]
+    Unequip {
+        item: Entity,
+    },
+    #[serde(skip)]
+    Drop {
+        item: Entity,
+    },
+    PickUp,
+    OperateDoor {
+        action: DoorAction,
+        x: i32,
+        y: i32,
+    },
+    Bash {
+        x: i32,
+        y: i32,
+    },
+    Cast {
+        spell: usize,
+        x: i32,
+        y: i32,
+    },
 }
 
 #[derive(Component, Debug, Serialize, Deserialize)]
@@ -306,9 +449,36 @@ pub enum MovementType {
     Random,
 }
 
+/// The tiles an entity can currently see.
+/// Recalculated by the visibility system when the entity moves or `dirty` is set.
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+pub struct Viewshed {
+    pub visible_tiles: Vec<(i32, i32)>,
+    pub range: i32,
+    pub dirty: bool,
+}
+
+/// A multi-turn activity an entity is busy with.
+/// Every turn the activity system turns this into a new `Intent`, until the activity is finished or interrupted.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activity {
+    Resting,
+    AutoExploring,
+}
+
+impl Activity {
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Self::Resting => "resting",
+            Self::AutoExploring => "exploring",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gamelogic::{CheckModifiers, Difficulty, RollResult, RollResultType};
 
     #[test]
     fn new_pool_is_correct() {
@@ -395,6 +565,18 @@ mod tests {
         assert!(pool.is_full());
     }
 
+    #[test]
+    fn skill_points_can_be_allocated() {
+        let mut skills = Skills {
+            unspent: 1,
+            ..Default::default()
+        };
+        assert!(skills.allocate(Skill::Melee));
+        assert_eq!(skills.get(Skill::Melee), 1);
+        assert!(!skills.allocate(Skill::Magic));
+        assert_eq!(skills.get(Skill::Magic), 0);
+    }
+
     #[test]
     fn attribute_roll_test() {
         let a = Attribute {
@@ -404,7 +586,18 @@ mod tests {
         };
 
         let result = a.check(89);
-        println!("result: {:?}", result);
         assert_eq!(result, RollResult::Success(RollResultType::Normal));
     }
+
+    #[test]
+    fn attribute_effective_value_includes_skill_and_difficulty() {
+        let a = Attribute {
+            base: 40,
+            modifiers: -5,
+            bonus: 10,
+        };
+        assert_eq!(a.effective_value(&CheckModifiers::default()), 45);
+        let modifiers = CheckModifiers::skill(2).with_difficulty(Difficulty::Hard);
+        assert_eq!(a.effective_value(&modifiers), 35);
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{Config, GameConfig, PlayerConfig, WorldConfig};
+use crate::{
+    Config, ExperienceConfig, GameConfig, ItemsConfig, LootConfig, PlayerConfig, SpellsConfig, TilesConfig, WorldConfig,
+};
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -13,6 +15,11 @@ impl ConfigMaster {
                 game: GameConfig::new(),
                 player: PlayerConfig::new(),
                 world: WorldConfig::new(),
+                experience: ExperienceConfig::new(),
+                spells: SpellsConfig::new(),
+                items: ItemsConfig::new(),
+                loot: LootConfig::new(),
+                tiles: TilesConfig::new(),
             },
         }
     }
@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ExperienceConfig {
+    pub kill_xp: i32,
+    pub level_base: i32,
+    pub level_exponent: f32,
+    pub max_level: i32,
+    pub hp_per_level: String,
+    pub mp_per_level: String,
+    pub attribute_gain: String,
+    pub skill_points_per_level: i32,
+}
+
+impl ExperienceConfig {
+    pub fn new() -> ExperienceConfig {
+        ExperienceConfig {
+            kill_xp: 10,
+            level_base: 20,
+            level_exponent: 1.5,
+            max_level: 30,
+            hp_per_level: "1d6".to_string(),
+            mp_per_level: "1d3".to_string(),
+            attribute_gain: "1d3".to_string(),
+            skill_points_per_level: 2,
+        }
+    }
+
+    /// Total experience needed to reach `level`.
+    pub fn xp_for_level(&self, level: i32) -> i32 {
+        if level <= 1 {
+            return 0;
+        }
+        (self.level_base as f32 * ((level - 1) as f32).powf(self.level_exponent)) as i32
+    }
+
+    /// Experience awarded for killing something of the given level.
+    pub fn xp_for_kill(&self, victim_level: i32) -> i32 {
+        self.kill_xp * victim_level.max(1)
+    }
+}
+
+impl Default for ExperienceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xp_curve_is_increasing() {
+        let cfg = ExperienceConfig::new();
+        assert_eq!(cfg.xp_for_level(1), 0);
+        assert_eq!(cfg.xp_for_level(2), 20);
+        assert_eq!(cfg.xp_for_level(3), 56);
+        for level in 2..cfg.max_level {
+            assert!(cfg.xp_for_level(level + 1) > cfg.xp_for_level(level));
+        }
+    }
+
+    #[test]
+    fn kill_xp_scales_with_victim_level() {
+        let cfg = ExperienceConfig::new();
+        assert_eq!(cfg.xp_for_kill(1), 10);
+        assert_eq!(cfg.xp_for_kill(3), 30);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::effect::{Area, Effect};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ItemsConfig {
+    pub items: Vec<ItemDefinition>,
+    /// Names shown for unidentified items of each class. They are shuffled for every game.
+    #[serde(default)]
+    pub appearances: HashMap<ItemClass, Vec<String>>,
+}
+
+/// Broad kind of an item. Decides how using it is described.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemClass {
+    Potion,
+    Scroll,
+    Wand,
+    Throwable,
+    Weapon,
+    /// Fired from launchers, e.g. arrows from bows.
+    Ammo,
+    Armor,
+    Ring,
+    Amulet,
+    /// Opens locked doors.
+    Key,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub glyph: char,
+    pub class: ItemClass,
+    #[serde(default)]
+    pub description: String,
+    /// Items with charges are used up when the last charge is spent, instead of one item of a stack.
+    #[serde(default)]
+    pub charges: Option<i32>,
+    /// Effects of using (drinking, reading, zapping) the item. Items without effects can't be used.
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// Where the effects of using the item happen.
+    #[serde(default)]
+    pub area: Area,
+    /// How far away (in tiles) the item can be aimed when it's used or thrown, or fired for launchers.
+    #[serde(default)]
+    pub range: i32,
+    /// Items which can be thrown.
+    #[serde(default)]
+    pub throw: Option<ThrowDefinition>,
+    /// Chances of the item being blessed or cursed when it's spawned.
+    #[serde(default)]
+    pub buc: BucChances,
+    /// Items which can be worn or wielded.
+    #[serde(default)]
+    pub equipment: Option<EquipmentDefinition>,
+}
+
+/// Where on the body an item is worn.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EquipSlot {
+    Weapon,
+    Body,
+    Legs,
+    Feet,
+    Head,
+    Hands,
+    Ring,
+    Amulet,
+}
+
+impl EquipSlot {
+    /// How many items can be worn in the slot at the same time.
+    pub fn capacity(&self) -> usize {
+        match self {
+            EquipSlot::Ring => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// What an item does for whoever wears or wields it.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EquipmentDefinition {
+    pub slot: EquipSlot,
+    /// Damage dealt in melee, for weapons.
+    #[serde(default)]
+    pub damage: Option<String>,
+    /// Skill points added to melee attacks.
+    #[serde(default)]
+    pub to_hit: i32,
+    /// Damage dealt to walls and doors when bashing them, for weapons which can dig, as a dice string.
+    #[serde(default)]
+    pub digging: Option<String>,
+    /// Defense value, making the wearer harder to hit. Counts like points of defense skill.
+    #[serde(default)]
+    pub dv: i32,
+    /// Protection value, reducing the damage the wearer takes.
+    #[serde(default)]
+    pub pv: i32,
+    /// Bonuses to the wearer's attributes.
+    #[serde(default)]
+    pub attributes: AttributeBonus,
+    /// Weapons which fire ammunition, e.g. bows.
+    #[serde(default)]
+    pub launcher: Option<LauncherDefinition>,
+}
+
+/// What a launcher fires. Fired ammunition is used up.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LauncherDefinition {
+    /// The name of the item fired.
+    pub ammo: String,
+    /// Damage dealt to whatever the ammunition hits.
+    pub damage: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttributeBonus {
+    #[serde(default)]
+    pub str: i32,
+    #[serde(default)]
+    pub dex: i32,
+    #[serde(default)]
+    pub con: i32,
+    #[serde(default)]
+    pub int: i32,
+}
+
+/// Chances (in percent) of an item being spawned blessed or cursed. Otherwise it's uncursed.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BucChances {
+    pub blessed: i32,
+    pub cursed: i32,
+}
+
+impl Default for BucChances {
+    fn default() -> Self {
+        Self { blessed: 10, cursed: 10 }
+    }
+}
+
+/// What happens when an item is thrown. Thrown items are used up.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ThrowDefinition {
+    /// Damage dealt to whatever the item hits.
+    pub damage: String,
+    /// Shown when the item lands, e.g. when it explodes.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Where the effects happen, around the point of impact.
+    #[serde(default)]
+    pub area: Area,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+}
+
+impl ItemsConfig {
+    pub fn new() -> ItemsConfig {
+        ItemsConfig {
+            items: Vec::new(),
+            appearances: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|i| i.name == name)
+    }
+}
+
+impl Default for ItemsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ItemClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemClass::Potion => "Potion",
+            ItemClass::Scroll => "Scroll",
+            ItemClass::Wand => "Wand",
+            ItemClass::Throwable => "Throwable",
+            ItemClass::Weapon => "Weapon",
+            ItemClass::Ammo => "Ammo",
+            ItemClass::Armor => "Armor",
+            ItemClass::Ring => "Ring",
+            ItemClass::Amulet => "Amulet",
+            ItemClass::Key => "Key",
+        }
+    }
+
+    /// Whether items of this class have to be identified before their names are known.
+    pub fn unidentified(&self) -> bool {
+        matches!(self, ItemClass::Potion | ItemClass::Scroll | ItemClass::Wand)
+    }
+}
+
+impl ItemDefinition {
+    pub fn usable(&self) -> bool {
+        !self.effects.is_empty()
+    }
+
+    pub fn equippable(&self) -> bool {
+        self.equipment.is_some()
+    }
+
+    /// Describes using the item, e.g. "You drink the murky potion."
+    /// Takes the name the item is shown with, since it may not have been identified yet.
+    pub fn use_message(&self, name: &str) -> String {
+        let verb = match self.class {
+            ItemClass::Potion => "drink",
+            ItemClass::Scroll => "read",
+            ItemClass::Wand => "zap",
+            _ => "use",
+        };
+        format!("You {} the {}.", verb, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_items_parse() {
+        let contents = std::fs::read_to_string("data/items.ron").unwrap();
+        let cfg: ItemsConfig = ron::de::from_str(&contents).unwrap();
+        let potion = cfg.get("potion of healing").unwrap();
+        assert!(potion.usable());
+        assert_eq!(potion.use_message("murky potion"), "You drink the murky potion.");
+        assert!(potion.class.unidentified());
+        let rock = cfg.get("exploding rock").unwrap();
+        assert!(!rock.usable());
+        assert_eq!(rock.throw.as_ref().unwrap().area, Area::Radius(1));
+        assert!(!rock.class.unidentified());
+        assert_eq!(rock.buc, BucChances::default());
+        assert_eq!(cfg.get("rusty longsword").unwrap().buc.cursed, 40);
+        let bow = cfg.get("short bow").unwrap().equipment.as_ref().unwrap();
+        let launcher = bow.launcher.as_ref().unwrap();
+        assert_eq!(cfg.get(&launcher.ammo).unwrap().class, ItemClass::Ammo);
+        let boots = cfg.get("old boots").unwrap().equipment.as_ref().unwrap();
+        assert_eq!(boots.slot, EquipSlot::Feet);
+        assert_eq!(boots.attributes, AttributeBonus::default());
+        for item in cfg.items.iter().filter(|i| {
+            matches!(
+                i.class,
+                ItemClass::Weapon | ItemClass::Armor | ItemClass::Ring | ItemClass::Amulet
+            )
+        }) {
+            assert!(item.equippable(), "{} can't be equipped", item.name);
+        }
+        for class in [ItemClass::Potion, ItemClass::Scroll, ItemClass::Wand] {
+            let kinds = cfg.items.iter().filter(|i| i.class == class).count();
+            assert!(
+                cfg.appearances[&class].len() >= kinds,
+                "not enough appearances for {:?}",
+                class
+            );
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::rng;
+
+/// How deep tables may refer to other tables. Deeper nesting (or a table referring to itself)
+/// rolls nothing.
+const MAX_NESTING: usize = 8;
+
+/// Which items lie around on each level, and what monsters drop when they die.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LootConfig {
+    /// Named tables, which can refer to each other.
+    pub tables: HashMap<String, Vec<LootEntry>>,
+    /// Which table is used on which levels. The first one covering the depth is used.
+    pub levels: Vec<LevelLoot>,
+    /// Item put on every level with locked doors, so they can be opened.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// The loot for a range of levels.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LevelLoot {
+    pub min_depth: usize,
+    pub max_depth: usize,
+    pub table: String,
+    /// How many items are scattered across the rooms of the level, as a dice string.
+    pub items: String,
+    /// Chance (in percent) of a monster dropping something from the table when it dies.
+    pub drop_chance: i32,
+}
+
+/// One entry of a loot table.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LootEntry {
+    pub loot: Loot,
+    #[serde(default)]
+    pub rarity: Rarity,
+    /// Size of the stack, as a dice string. A single item if not given.
+    /// Ignored for nested tables, whose own entries decide the stack size.
+    #[serde(default)]
+    pub count: Option<String>,
+    /// Shallowest level the entry can be rolled on.
+    #[serde(default)]
+    pub min_depth: usize,
+    /// Deepest level the entry can be rolled on.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Loot {
+    /// An item, by name.
+    Item(String),
+    /// Rolls again on another table.
+    Table(String),
+    /// No loot at all.
+    Nothing,
+}
+
+/// How often an entry is rolled compared to the others in its table.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    VeryRare,
+    /// An explicit weight, for when the named rarities don't fit.
+    Weight(i32),
+}
+
+impl Rarity {
+    pub fn weight(&self) -> i32 {
+        match self {
+            Rarity::Common => 50,
+            Rarity::Uncommon => 20,
+            Rarity::Rare => 8,
+            Rarity::VeryRare => 2,
+            Rarity::Weight(weight) => (*weight).max(0),
+        }
+    }
+}
+
+impl LootEntry {
+    fn available_at(&self, depth: usize) -> bool {
+        depth >= self.min_depth && self.max_depth.is_none_or(|max| depth <= max)
+    }
+}
+
+impl LootConfig {
+    pub fn new() -> LootConfig {
+        LootConfig {
+            tables: HashMap::new(),
+            levels: Vec::new(),
+            key: None,
+        }
+    }
+
+    /// Returns the loot for the level at the given depth.
+    pub fn level(&self, depth: usize) -> Option<&LevelLoot> {
+        self.levels.iter().find(|l| depth >= l.min_depth && depth <= l.max_depth)
+    }
+
+    /// Rolls on the named table for something found at the given depth. Returns the name of the
+    /// item and the size of the stack, or None if the roll came up empty.
+    pub fn roll(&self, table: &str, depth: usize) -> Option<(String, i32)> {
+        self.roll_nested(table, depth, 0)
+    }
+
+    fn roll_nested(&self, table: &str, depth: usize, nesting: usize) -> Option<(String, i32)> {
+        if nesting > MAX_NESTING {
+            return None;
+        }
+        let entries: Vec<&LootEntry> = self
+            .tables
+            .get(table)?
+            .iter()
+            .filter(|e| e.available_at(depth) && e.rarity.weight() > 0)
+            .collect();
+        let total: i32 = entries.iter().map(|e| e.rarity.weight()).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng::range(0, total);
+        let entry = entries.into_iter().find(|e| {
+            roll -= e.rarity.weight();
+            roll < 0
+        })?;
+        match &entry.loot {
+            Loot::Item(name) => {
+                let count = entry.count.as_ref().map_or(1, rng::roll_str).max(1);
+                Some((name.clone(), count))
+            }
+            Loot::Table(name) => self.roll_nested(name, depth, nesting + 1),
+            Loot::Nothing => None,
+        }
+    }
+}
+
+impl Default for LootConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ItemsConfig;
+
+    fn entry(loot: Loot) -> LootEntry {
+        LootEntry {
+            loot,
+            rarity: Rarity::Common,
+            count: None,
+            min_depth: 0,
+            max_depth: None,
+        }
+    }
+
+    #[test]
+    fn nested_tables_and_depth_limits() {
+        let mut cfg = LootConfig::new();
+        let mut deep = entry(Loot::Item("deep thing".to_string()));
+        deep.min_depth = 5;
+        let mut stack = entry(Loot::Item("rock".to_string()));
+        stack.count = Some("2d1".to_string());
+        cfg.tables
+            .insert("outer".to_string(), vec![entry(Loot::Table("inner".to_string())), deep]);
+        cfg.tables.insert("inner".to_string(), vec![stack]);
+        cfg.tables
+            .insert("loop".to_string(), vec![entry(Loot::Table("loop".to_string()))]);
+        cfg.tables.insert("empty".to_string(), vec![entry(Loot::Nothing)]);
+
+        for _ in 0..20 {
+            assert_eq!(cfg.roll("outer", 1), Some(("rock".to_string(), 2)));
+        }
+        assert_eq!(cfg.roll("loop", 1), None);
+        assert_eq!(cfg.roll("empty", 1), None);
+        assert_eq!(cfg.roll("missing", 1), None);
+    }
+
+    #[test]
+    fn bundled_loot_parses() {
+        let contents = std::fs::read_to_string("data/loot.ron").unwrap();
+        let cfg: LootConfig = ron::de::from_str(&contents).unwrap();
+        let contents = std::fs::read_to_string("data/items.ron").unwrap();
+        let items: ItemsConfig = ron::de::from_str(&contents).unwrap();
+
+        for (name, entries) in cfg.tables.iter() {
+            for entry in entries.iter() {
+                match &entry.loot {
+                    Loot::Item(item) => assert!(items.get(item).is_some(), "{name}: unknown item {item}"),
+                    Loot::Table(table) => assert!(cfg.tables.contains_key(table), "{name}: unknown table {table}"),
+                    Loot::Nothing => {}
+                }
+            }
+        }
+        for level in cfg.levels.iter() {
+            assert!(cfg.tables.contains_key(&level.table));
+        }
+        assert!(cfg.level(1).is_some());
+        assert!(cfg.key.is_some_and(|key| items.get(&key).is_some()));
+    }
+}
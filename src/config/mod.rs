@@ -1,54 +1,135 @@
 use bevy::log::{debug, info};
-use serde::Deserialize;
-use std::fs;
+use serde::{Deserialize, de::DeserializeOwned};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
 use std::sync::Mutex;
 
 mod configmaster;
+mod experience;
 mod game;
+mod items;
+mod loot;
+mod paths;
 mod player;
+mod spells;
+mod tiles;
 mod ui;
+mod validate;
 mod world;
 
 pub use configmaster::*;
+pub use experience::*;
 pub use game::*;
+pub use items::*;
+pub use loot::*;
+pub use paths::*;
 pub use player::*;
+pub use spells::*;
+pub use tiles::*;
 pub use ui::*;
+pub use validate::*;
 pub use world::*;
 
 lazy_static! {
     pub static ref CFG: Mutex<ConfigMaster> = Mutex::new(ConfigMaster::new());
 }
 
-const CONFIG_DIR: &str = "config";
-const DATA_DIR: &str = "data";
+pub(crate) const CONFIG_DIR: &str = "config";
+pub(crate) const DATA_DIR: &str = "data";
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Config {
     pub game: GameConfig,
     pub player: PlayerConfig,
     pub world: WorldConfig,
+    pub experience: ExperienceConfig,
+    pub spells: SpellsConfig,
+    pub items: ItemsConfig,
+    pub loot: LootConfig,
+    pub tiles: TilesConfig,
 }
 
-pub fn load_config(config_path: Option<&str>, data_path: Option<&str>) {
-    let config_path = config_path.unwrap_or(CONFIG_DIR);
-    let data_path = data_path.unwrap_or(DATA_DIR);
+/// Loads the config and data files, from where the paths say they are. Nothing is loaded if any of
+/// them is missing, can't be parsed or fails validation, and the report lists everything wrong.
+pub fn load_config(paths: &Paths) -> Result<(), ConfigReport> {
+    info!(
+        "Loading config files from {:?} and data files from {:?}",
+        paths.config_dirs, paths.data_dirs
+    );
+    let mut report = ConfigReport::default();
+    let mut files = HashMap::new();
+    let gameconfig: Option<GameConfig> = read_ron("game.ron", paths.read_config("game.ron"), &mut files, &mut report);
+    let playerconfig: Option<PlayerConfig> =
+        read_ron("player.ron", paths.read_config("player.ron"), &mut files, &mut report);
+    let worldconfig: Option<WorldConfig> = read_ron("world.ron", paths.read_data("world.ron"), &mut files, &mut report);
+    let experienceconfig: Option<ExperienceConfig> =
+        read_ron("experience.ron", paths.read_data("experience.ron"), &mut files, &mut report);
+    let spellsconfig: Option<SpellsConfig> = read_ron("spells.ron", paths.read_data("spells.ron"), &mut files, &mut report);
+    let itemsconfig: Option<ItemsConfig> = read_ron("items.ron", paths.read_data("items.ron"), &mut files, &mut report);
+    let lootconfig: Option<LootConfig> = read_ron("loot.ron", paths.read_data("loot.ron"), &mut files, &mut report);
+    let tilesconfig: Option<TilesConfig> = read_ron("tiles.ron", paths.read_data("tiles.ron"), &mut files, &mut report);
 
-    info!("Loading config files from path: '{}'", config_path);
-    debug!("Loading game config file");
-    let contents = fs::read_to_string(format!("{}/game.ron", config_path)).expect("Failed to read game config file");
-    let gameconfig: GameConfig = ron::de::from_str(&contents).expect("Failed to parse game config file");
+    let (Some(game), Some(player), Some(world), Some(experience), Some(spells), Some(items), Some(loot), Some(tiles)) = (
+        gameconfig,
+        playerconfig,
+        worldconfig,
+        experienceconfig,
+        spellsconfig,
+        itemsconfig,
+        lootconfig,
+        tilesconfig,
+    ) else {
+        return Err(report);
+    };
+    let config = Config {
+        game,
+        player,
+        world,
+        experience,
+        spells,
+        items,
+        loot,
+        tiles: tiles.completed(),
+    };
+    validate_config(&config, &files, &mut report);
+    report.into_result()?;
 
-    debug!("Loading player config file");
-    let contents = fs::read_to_string(format!("{}/player.ron", config_path)).expect("Failed to read player config file");
-    let playerconfig: PlayerConfig = ron::de::from_str(&contents).expect("Failed to parse player config file");
+    CFG.lock().unwrap().load(config);
+    Ok(())
+}
 
-    debug!("Loading world data file");
-    let contents = fs::read_to_string(format!("{}/world.ron", data_path)).expect("Failed to read world data file");
-    let worldconfig: WorldConfig = ron::de::from_str(&contents).expect("Failed to parse world data file");
+/// Parses a config or data file read by `Paths`, adding to the report if that fails. Remembers
+/// where the file was read from, for the problems found when validating it.
+fn read_ron<T: DeserializeOwned>(
+    name: &'static str,
+    file: io::Result<(Cow<'static, str>, String)>,
+    files: &mut HashMap<&'static str, String>,
+    report: &mut ConfigReport,
+) -> Option<T> {
+    let (contents, origin) = match file {
+        Ok(file) => file,
+        Err(e) => {
+            report.add(name, "", format!("can't be read: {e}"));
+            return None;
+        }
+    };
+    debug!("Loading {name} from {origin}");
+    files.insert(name, origin.clone());
+    parse_ron(&contents, &origin, report)
+}
 
-    CFG.lock().unwrap().load(Config {
-        game: gameconfig,
-        player: playerconfig,
-        world: worldconfig,
-    });
+/// Parses a config or data file, adding the line and column to the report if that fails.
+fn parse_ron<T: DeserializeOwned>(contents: &str, origin: &str, report: &mut ConfigReport) -> Option<T> {
+    match ron::de::from_str(contents) {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            report.add(
+                origin,
+                format!("line {}, column {}", e.position.line, e.position.col),
+                e.code.to_string(),
+            );
+            None
+        }
+    }
 }
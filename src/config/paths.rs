@@ -0,0 +1,177 @@
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use super::{CONFIG_DIR, DATA_DIR};
+
+/// Name of the game's directories under the user's config, data and state directories.
+const APP_DIR: &str = "relics";
+
+/// The config files bundled with the game, used when they aren't found in any config directory.
+const BUNDLED_CONFIG: [(&str, &str); 3] = [
+    ("game.ron", include_str!("../../config/game.ron")),
+    ("player.ron", include_str!("../../config/player.ron")),
+    ("ui_config.json", include_str!("../../config/ui_config.json")),
+];
+
+/// The data files bundled with the game, used when they aren't found in any data directory.
+const BUNDLED_DATA: [(&str, &str); 6] = [
+    ("world.ron", include_str!("../../data/world.ron")),
+    ("experience.ron", include_str!("../../data/experience.ron")),
+    ("spells.ron", include_str!("../../data/spells.ron")),
+    ("items.ron", include_str!("../../data/items.ron")),
+    ("loot.ron", include_str!("../../data/loot.ron")),
+    ("tiles.ron", include_str!("../../data/tiles.ron")),
+];
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Where the game reads its config and data files from, and where it writes its own files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paths {
+    /// Searched in order for config files, before falling back on the bundled ones.
+    pub config_dirs: Vec<PathBuf>,
+    /// Searched in order for data files, before falling back on the bundled ones.
+    pub data_dirs: Vec<PathBuf>,
+    /// Where the user's own configuration is written, e.g. key bindings changed in the game.
+    pub user_config_dir: Option<PathBuf>,
+    /// Where logs are written.
+    pub state_dir: PathBuf,
+}
+
+impl Paths {
+    /// Resolves the directories from the command line, the environment and the XDG base
+    /// directories. Directories given on the command line take precedence over `RELICS_CONFIG_DIR`,
+    /// `RELICS_DATA_DIR` and `RELICS_STATE_DIR`, which take precedence over the XDG directories
+    /// and finally `config/` and `data/` in the working directory, as in a source checkout.
+    pub fn resolve(config_dir: Option<PathBuf>, data_dir: Option<PathBuf>) -> Paths {
+        Self::resolve_with(config_dir, data_dir, |var| env::var_os(var))
+    }
+
+    fn resolve_with(
+        config_dir: Option<PathBuf>,
+        data_dir: Option<PathBuf>,
+        env: impl Fn(&str) -> Option<OsString>,
+    ) -> Paths {
+        let explicit = |cli: Option<PathBuf>, var| cli.or_else(|| env(var).map(PathBuf::from));
+        let config_dir = explicit(config_dir, "RELICS_CONFIG_DIR");
+        let data_dir = explicit(data_dir, "RELICS_DATA_DIR");
+        let xdg_config = xdg_dir(&env, "XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR));
+        let xdg_data = xdg_dir(&env, "XDG_DATA_HOME", ".local/share").map(|dir| dir.join(APP_DIR));
+        let xdg_state = xdg_dir(&env, "XDG_STATE_HOME", ".local/state").map(|dir| dir.join(APP_DIR));
+
+        let config_dirs = [config_dir.clone(), xdg_config.clone(), Some(PathBuf::from(CONFIG_DIR))];
+        let data_dirs = [data_dir, xdg_data, Some(PathBuf::from(DATA_DIR))];
+        Paths {
+            config_dirs: config_dirs.into_iter().flatten().collect(),
+            data_dirs: data_dirs.into_iter().flatten().collect(),
+            user_config_dir: config_dir.or(xdg_config),
+            state_dir: explicit(None, "RELICS_STATE_DIR")
+                .or(xdg_state)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+
+    /// Reads a config file from the first config directory which has it, or the bundled copy.
+    /// Also returns where the file was read from.
+    pub fn read_config(&self, name: &str) -> io::Result<(Cow<'static, str>, String)> {
+        read_file(&self.config_dirs, &BUNDLED_CONFIG, name)
+    }
+
+    /// Reads a data file from the first data directory which has it, or the bundled copy.
+    /// Also returns where the file was read from.
+    pub fn read_data(&self, name: &str) -> io::Result<(Cow<'static, str>, String)> {
+        read_file(&self.data_dirs, &BUNDLED_DATA, name)
+    }
+
+    /// The bundled copy of a config file.
+    pub fn bundled_config(name: &str) -> Option<&'static str> {
+        BUNDLED_CONFIG.iter().find(|(n, _)| *n == name).map(|(_, contents)| *contents)
+    }
+}
+
+/// Sets the paths used by the rest of the game. Only the first call has any effect.
+pub fn init_paths(paths: Paths) {
+    if PATHS.set(paths).is_err() {
+        bevy::log::warn!("Paths were already set, ignoring new ones");
+    }
+}
+
+/// The paths used by the game, resolved from the environment alone if they weren't set.
+pub fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::resolve(None, None))
+}
+
+fn read_file(dirs: &[PathBuf], bundled: &[(&str, &'static str)], name: &str) -> io::Result<(Cow<'static, str>, String)> {
+    for dir in dirs {
+        let path = dir.join(name);
+        match fs::read_to_string(&path) {
+            Ok(contents) => return Ok((Cow::Owned(contents), path.display().to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        }
+    }
+    match bundled.iter().find(|(n, _)| *n == name) {
+        Some((_, contents)) => Ok((Cow::Borrowed(*contents), format!("bundled {name}"))),
+        None => Err(io::Error::new(io::ErrorKind::NotFound, format!("{name} not found"))),
+    }
+}
+
+/// The XDG base directory in the environment variable, or its default under the home directory.
+/// Relative paths in the variable are invalid and ignored, as the spec requires.
+fn xdg_dir(env: &impl Fn(&str) -> Option<OsString>, var: &str, home_default: &str) -> Option<PathBuf> {
+    env(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env("HOME").map(|home| Path::new(&home).join(home_default)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |var| vars.iter().find(|(v, _)| *v == var).map(|(_, value)| OsString::from(value))
+    }
+
+    #[test]
+    fn command_line_beats_environment_beats_xdg() {
+        let vars = [
+            ("HOME", "/home/player"),
+            ("XDG_DATA_HOME", "/xdg/data"),
+            ("XDG_STATE_HOME", "relative/state"),
+            ("RELICS_CONFIG_DIR", "/env/config"),
+            ("RELICS_DATA_DIR", "/env/data"),
+        ];
+        let paths = Paths::resolve_with(Some(PathBuf::from("/cli/config")), None, env(&vars));
+        let dirs = |d: &[&str]| d.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(
+            paths.config_dirs,
+            dirs(&["/cli/config", "/home/player/.config/relics", "config"])
+        );
+        assert_eq!(paths.data_dirs, dirs(&["/env/data", "/xdg/data/relics", "data"]));
+        assert_eq!(paths.user_config_dir, Some(PathBuf::from("/cli/config")));
+        // Relative XDG directories are ignored
+        assert_eq!(paths.state_dir, PathBuf::from("/home/player/.local/state/relics"));
+    }
+
+    #[test]
+    fn missing_files_fall_back_on_the_bundled_ones() {
+        let paths = Paths::resolve_with(Some(PathBuf::from("/nonexistent")), None, env(&[]));
+        assert_eq!(paths.user_config_dir, Some(PathBuf::from("/nonexistent")));
+        assert_eq!(paths.state_dir, PathBuf::from("."));
+        let paths = Paths {
+            config_dirs: vec![PathBuf::from("/nonexistent")],
+            ..paths
+        };
+        let (contents, origin) = paths.read_config("game.ron").unwrap();
+        assert_eq!(contents, include_str!("../../config/game.ron"));
+        assert_eq!(origin, "bundled game.ron");
+        assert!(paths.read_data("nothing.ron").is_err());
+    }
+}
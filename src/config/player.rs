@@ -1,6 +1,8 @@
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
+use crate::component::Regen;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct PlayerConfig {
     pub name: String,
     pub hp: i32,
@@ -14,6 +16,9 @@ pub struct PlayerConfig {
     pub hp_regen: PlayerRegen,
     pub mp_regen: PlayerRegen,
     pub equipment: PlayerEquipment,
+    /// Names of the spells the player knows from the start.
+    #[serde(default)]
+    pub spells: Vec<String>,
 }
 
 impl PlayerConfig {
@@ -39,6 +44,7 @@ impl PlayerConfig {
                 carried: Vec::new(),
                 carried_stacks: Vec::new(),
             },
+            spells: Vec::new(),
         }
     }
 }
@@ -49,26 +55,35 @@ impl Default for PlayerConfig {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct PlayerSkills {
     pub melee: i32,
     pub defense: i32,
     pub magic: i32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct PlayerRegen {
     pub frequency: i32,
     pub amount: i32,
 }
 
-#[derive(Deserialize, Debug)]
+impl From<&PlayerRegen> for Regen {
+    fn from(regen: &PlayerRegen) -> Self {
+        Regen {
+            frequency: regen.frequency,
+            amount: regen.amount,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct ItemStack {
     pub name: String,
     pub amount: i32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct PlayerEquipment {
     pub equipped: Vec<String>,
     pub carried: Vec<String>,
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+
+use crate::{
+    effect::{Area, Effect},
+    gamelogic::Difficulty,
+};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SpellsConfig {
+    pub spells: Vec<SpellDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SpellDefinition {
+    pub name: String,
+    pub description: String,
+    /// Magic points needed to cast the spell.
+    pub cost: i32,
+    /// How far away (in tiles) the spell can be aimed.
+    #[serde(default)]
+    pub range: i32,
+    #[serde(default)]
+    pub area: Area,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub effects: Vec<Effect>,
+}
+
+impl SpellsConfig {
+    pub fn new() -> SpellsConfig {
+        SpellsConfig { spells: Vec::new() }
+    }
+
+    /// Returns the id (index) of the spell with the given name.
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.spells.iter().position(|s| s.name == name)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&SpellDefinition> {
+        self.spells.get(id)
+    }
+}
+
+impl Default for SpellsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_spells_parse() {
+        let contents = std::fs::read_to_string("data/spells.ron").unwrap();
+        let cfg: SpellsConfig = ron::de::from_str(&contents).unwrap();
+        let id = cfg.id("magic missile").unwrap();
+        let spell = cfg.get(id).unwrap();
+        assert_eq!(spell.area, Area::Target);
+        assert_eq!(spell.effects, vec![Effect::Damage("1d6".to_string())]);
+        assert!(cfg.id("no such spell").is_none());
+    }
+}
@@ -0,0 +1,128 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::map::TileType;
+
+/// What the tiles of the maps look like and how they behave.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TilesConfig {
+    pub tiles: Vec<TileDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TileDefinition {
+    pub id: TileType,
+    pub name: String,
+    pub glyph: char,
+    /// Colors while the tile is in view.
+    pub fg: Color,
+    #[serde(default = "black")]
+    pub bg: Color,
+    /// Colors once the tile has been seen, but is out of view.
+    pub remembered_fg: Color,
+    #[serde(default = "black")]
+    pub remembered_bg: Color,
+    #[serde(default)]
+    pub walkable: bool,
+    #[serde(default)]
+    pub blocks_view: bool,
+    /// How much damage the tile takes before it's destroyed. Tiles without hitpoints can't be destroyed.
+    #[serde(default)]
+    pub hitpoints: Option<i32>,
+    /// What is left of the tile once it's destroyed.
+    #[serde(default)]
+    pub destroyed: Option<TileType>,
+    #[serde(default)]
+    pub description: String,
+}
+
+fn black() -> Color {
+    Color::Black
+}
+
+impl TileDefinition {
+    /// Stands in for tile types missing from the definitions, so they show up on the map.
+    fn missing(id: TileType) -> TileDefinition {
+        TileDefinition {
+            id,
+            name: "unknown tile".to_string(),
+            glyph: '?',
+            fg: Color::Red,
+            bg: Color::Black,
+            remembered_fg: Color::Red,
+            remembered_bg: Color::Black,
+            walkable: false,
+            blocks_view: true,
+            hitpoints: None,
+            destroyed: None,
+            description: String::new(),
+        }
+    }
+}
+
+impl TilesConfig {
+    /// The tile definitions bundled with the game. Maps need them for every tile type they use,
+    /// so they're what the maps fall back on until the data files are loaded.
+    pub fn new() -> TilesConfig {
+        let tiles: TilesConfig =
+            ron::de::from_str(include_str!("../../data/tiles.ron")).expect("Failed to parse bundled tiles data file");
+        tiles.completed()
+    }
+
+    /// Looks up the definition of a tile type. Once completed, the definitions are a table in the
+    /// order of the tile types, so this doesn't need to search.
+    pub fn get(&self, id: TileType) -> Option<&TileDefinition> {
+        match self.tiles.get(id as usize) {
+            Some(tile) if tile.id == id => Some(tile),
+            _ => self.tiles.iter().find(|t| t.id == id),
+        }
+    }
+
+    /// Returns the definitions as a table with one entry for every tile type, in their order.
+    /// Tile types without a definition get a stand-in, and only the first definition of a tile
+    /// type is used.
+    pub fn completed(self) -> TilesConfig {
+        let tiles = TileType::ALL
+            .iter()
+            .map(|id| self.get(*id).cloned().unwrap_or_else(|| TileDefinition::missing(*id)))
+            .collect();
+        TilesConfig { tiles }
+    }
+}
+
+impl Default for TilesConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_tiles_cover_every_tile_type() {
+        let contents = std::fs::read_to_string("data/tiles.ron").unwrap();
+        let cfg: TilesConfig = ron::de::from_str(&contents).unwrap();
+        for id in TileType::ALL {
+            assert!(cfg.get(id).is_some(), "no definition for {id:?}");
+        }
+        for tile in cfg.tiles.iter() {
+            assert!(tile.destroyed.is_none_or(|d| cfg.get(d).is_some()));
+            assert_eq!(tile.hitpoints.is_some(), tile.destroyed.is_some(), "{}", tile.name);
+        }
+    }
+
+    #[test]
+    fn completed_tiles_are_a_table_of_all_tile_types() {
+        let cfg = TilesConfig {
+            tiles: vec![TileDefinition::missing(TileType::Rubble)],
+        }
+        .completed();
+        assert_eq!(cfg.tiles.len(), TileType::ALL.len());
+        for (tile, id) in cfg.tiles.iter().zip(TileType::ALL) {
+            assert_eq!(tile.id, id);
+        }
+        assert_eq!(cfg.get(TileType::Floor).unwrap().glyph, '?');
+    }
+}
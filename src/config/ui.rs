@@ -1,13 +1,22 @@
 use anyhow::Result;
-use bevy::log::{debug, warn};
+use bevy::log::debug;
 use bevy_ecs::resource::Resource;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use ratatui::style::{Color, Modifier, Style};
-use serde::{Deserialize, de::Deserializer};
-use std::{collections::HashMap, path::PathBuf};
+use serde::{
+    Deserialize, Serialize, Serializer,
+    de::{self, Deserializer, IntoDeserializer, value::StrDeserializer},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
 
-use crate::{GameEvent, GameState};
+use super::{ConfigReport, Paths, paths};
+
+use crate::{GameEvent, GameState, MenuState, TurnState};
 
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct AppConfig {
@@ -17,30 +26,88 @@ pub struct AppConfig {
     pub _config_dir: PathBuf,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Resource)]
+#[derive(Clone, Debug, Deserialize, Resource)]
 pub struct UIConfig {
     #[serde(default, flatten)]
     pub config: AppConfig,
     #[serde(default)]
     pub keybindings: KeyBindings,
+    /// The key bindings from the config files, before the changes made in the game were applied.
+    #[serde(skip)]
+    pub default_keybindings: KeyBindings,
+    /// How keys fall through from one input context to the one below it.
+    #[serde(default)]
+    pub input_contexts: HashMap<InputContext, InputContextConfig>,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// How long to wait for the next key of a key sequence, in milliseconds.
+    #[serde(default = "default_key_sequence_timeout")]
+    pub key_sequence_timeout: u64,
 }
 
-impl UIConfig {
-    // todo: we could impl default, to let bevy init_resource auto-load
-    pub fn new() -> Result<Self, config::ConfigError> {
-        // let default_config: UIConfig = json5::from_str(UICONFIG).unwrap();
-        // let data_dir = crate::utils::get_data_dir();
-        let data_dir: PathBuf = PathBuf::from("/home/rolf/src/rolf/relics");
-        // let config_dir = crate::utils::get_config_dir();
-        let config_dir: PathBuf = PathBuf::from("/home/rolf/src/rolf/relics/config");
-        let mut builder = config::Config::builder()
-            .set_default("_data_dir", data_dir.to_str().unwrap())?
-            .set_default("_config_dir", config_dir.to_str().unwrap())?;
+fn default_key_sequence_timeout() -> u64 {
+    1000
+}
+
+/// How the game screen is divided between the map, the log and the sidebar.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Percentage of the screen width used for the map and log, the sidebar gets the rest.
+    pub map_width: u16,
+    /// Percentage of the map and log column used for the map, the log gets the rest.
+    pub map_height: u16,
+    /// The smallest terminal the game screen is drawn in, smaller terminals get a warning instead.
+    pub min_width: u16,
+    pub min_height: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            map_width: 67,
+            map_height: 67,
+            min_width: 60,
+            min_height: 20,
+        }
+    }
+}
+
+impl Default for UIConfig {
+    fn default() -> Self {
+        Self {
+            config: AppConfig::default(),
+            keybindings: KeyBindings::default(),
+            default_keybindings: KeyBindings::default(),
+            input_contexts: HashMap::new(),
+            styles: Styles::default(),
+            layout: LayoutConfig::default(),
+            key_sequence_timeout: default_key_sequence_timeout(),
+        }
+    }
+}
 
-        // TODO: this tries to read ALL of these files!
-        // The latter entries take precedence over the previous
+impl UIConfig {
+    /// Loads the UI config, layering the config directories over the bundled config and the key
+    /// bindings changed in the game over both. Every file is checked before anything is merged, so
+    /// the report can say which file each problem is in.
+    pub fn new() -> Result<Self, ConfigReport> {
+        let paths = paths();
+        let mut report = ConfigReport::default();
+
+        // The bundled config comes first, so the config directories only need what they change.
+        // The latter entries take precedence over the previous, and the config directories are
+        // searched in order, so the first one is added last.
+        let mut sources = Vec::new();
+        if let Some(bundled) = Paths::bundled_config("ui_config.json") {
+            sources.push((
+                "bundled ui_config.json".to_string(),
+                bundled.to_string(),
+                config::FileFormat::Json,
+            ));
+        }
         let config_files = [
             ("ui_config.json5", config::FileFormat::Json5),
             ("ui_config.json", config::FileFormat::Json),
@@ -49,21 +116,64 @@ impl UIConfig {
             ("ui_config.toml", config::FileFormat::Toml),
             ("ui_config.ini", config::FileFormat::Ini),
         ];
-        let mut found_config = false;
-        for (file, format) in &config_files {
-            builder = builder.add_source(config::File::from(config_dir.join(file)).format(*format).required(false));
-            if config_dir.join(file).exists() {
-                found_config = true
+        for dir in paths.config_dirs.iter().rev() {
+            for (file, format) in &config_files {
+                let path = dir.join(file);
+                if path.exists() {
+                    debug!("Loading ui config from {}", path.display());
+                    match std::fs::read_to_string(&path) {
+                        Ok(contents) => sources.push((path.display().to_string(), contents, *format)),
+                        Err(e) => report.add(&path.display().to_string(), "", format!("can't be read: {e}")),
+                    }
+                }
             }
         }
-        if !found_config {
-            warn!("No ui configuration file found. Application may not behave as expected");
+
+        let data_dir = paths.data_dirs.first().cloned().unwrap_or_default();
+        let config_dir = paths.user_config_dir.clone().unwrap_or_default();
+        let mut builder = config::Config::builder()
+            .set_default("_data_dir", data_dir.to_string_lossy().as_ref())
+            .and_then(|builder| builder.set_default("_config_dir", config_dir.to_string_lossy().as_ref()))
+            .map_err(|e| problem("ui config", e))?;
+        for (origin, contents, format) in sources {
+            let source = config::File::from_str(&contents, format);
+            match config::Config::builder().add_source(source.clone()).build() {
+                Ok(single) => check_config_bindings(&mut report, &origin, &single),
+                Err(e) => report.add(&origin, "", e.to_string()),
+            }
+            builder = builder.add_source(source);
+        }
+
+        // Key bindings changed in the game are applied over the configured ones, key by key
+        let mut user_bindings = None;
+        if let Some(path) = user_keybindings_path().filter(|p| p.exists()) {
+            debug!("Loading user key bindings from {}", path.display());
+            let origin = path.display().to_string();
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => match serde_json::from_str::<HashMap<String, HashMap<String, serde_json::Value>>>(&contents)
+                {
+                    Ok(raw) => {
+                        // null unbinds a key
+                        check_bindings(&mut report, &origin, raw, |event| match event {
+                            serde_json::Value::Null => Ok(None),
+                            event => GameEvent::deserialize(event).map(Some).map_err(|e| e.to_string()),
+                        });
+                        user_bindings = Some((origin, contents));
+                    }
+                    Err(e) => report.add(&origin, "", e.to_string()),
+                },
+                Err(e) => report.add(&origin, "", format!("can't be read: {e}")),
+            }
         }
+        report.clone().into_result()?;
 
-        let mut cfg: Self = builder.build()?.try_deserialize()?;
+        let mut cfg: Self = builder
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .map_err(|e| problem("ui config", e))?;
 
         for (mode, default_bindings) in cfg.clone().keybindings.iter() {
-            let user_bindings = cfg.keybindings.entry(*mode).or_default();
+            let user_bindings = cfg.keybindings.entry(mode.clone()).or_default();
             for (key, cmd) in default_bindings.iter() {
                 user_bindings.entry(key.clone()).or_insert_with(|| cmd.clone());
             }
@@ -77,36 +187,275 @@ impl UIConfig {
             }
         }
 
+        cfg.default_keybindings = cfg.keybindings.clone();
+        if let Some((origin, contents)) = user_bindings {
+            let changes: KeyBindingChanges = serde_json::from_str(&contents).map_err(|e| problem(&origin, e))?;
+            changes.apply(&mut cfg.keybindings);
+        }
+
+        for (field, value) in [
+            ("layout.map_width", cfg.layout.map_width),
+            ("layout.map_height", cfg.layout.map_height),
+        ] {
+            if !(1..=100).contains(&value) {
+                report.add("ui config", field, format!("must be a percentage from 1 to 100, not {value}"));
+            }
+        }
+        report.into_result()?;
+
         Ok(cfg)
     }
+
+    /// The key bindings which apply in a stack of input contexts, given from the top down. Keys
+    /// bound in a context hide those bound in the contexts below it, and keys it doesn't bind fall
+    /// through to them unless the context is configured not to.
+    pub fn keymap(&self, contexts: &[InputContext]) -> HashMap<Vec<KeyEvent>, GameEvent> {
+        let bottom = contexts
+            .iter()
+            .position(|c| self.input_contexts.get(c).is_some_and(|cfg| !cfg.fallthrough))
+            .map_or(contexts.len(), |i| i + 1);
+        let mut keymap = HashMap::new();
+        for context in contexts[..bottom].iter().rev() {
+            if let Some(bindings) = self.keybindings.get(context) {
+                keymap.extend(bindings.iter().map(|(keys, event)| (keys.clone(), event.clone())));
+            }
+        }
+        keymap
+    }
+}
+
+/// Where a set of key bindings applies. In the config these are named after the game state or
+/// sub-state (e.g. `InGame`, `PlayersTurn`), or the UI component (e.g. `level_up`), with `Global`
+/// applying everywhere.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum InputContext {
+    Global,
+    State(GameState),
+    Menu(MenuState),
+    Turn(TurnState),
+    Component(String),
+}
+
+impl TryFrom<String> for InputContext {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        fn variant<'de, T: Deserialize<'de>>(name: &'de str) -> Option<T> {
+            let deserializer: StrDeserializer<'de, serde::de::value::Error> = name.into_deserializer();
+            T::deserialize(deserializer).ok()
+        }
+        if name == "Global" {
+            Ok(InputContext::Global)
+        } else if let Some(state) = variant(&name) {
+            Ok(InputContext::State(state))
+        } else if let Some(state) = variant(&name) {
+            Ok(InputContext::Menu(state))
+        } else if let Some(state) = variant(&name) {
+            Ok(InputContext::Turn(state))
+        } else if !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            Ok(InputContext::Component(name))
+        } else {
+            Err(format!("unknown input context `{name}`"))
+        }
+    }
+}
+
+impl fmt::Display for InputContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputContext::Global => write!(f, "Global"),
+            InputContext::State(state) => write!(f, "{state:?}"),
+            InputContext::Menu(state) => write!(f, "{state:?}"),
+            InputContext::Turn(state) => write!(f, "{state:?}"),
+            InputContext::Component(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InputContextConfig {
+    /// Whether keys the context doesn't bind are looked up in the contexts below it.
+    #[serde(default = "fallthrough")]
+    pub fallthrough: bool,
+}
+
+fn fallthrough() -> bool {
+    true
+}
+
+/// The file key bindings changed in the game are saved to.
+pub fn user_keybindings_path() -> Option<PathBuf> {
+    paths().user_config_dir.as_ref().map(|dir| dir.join("keybindings.json"))
+}
+
+fn problem(file: &str, e: impl fmt::Display) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    report.add(file, "", e.to_string());
+    report
+}
+
+/// Checks the key bindings in one UI config file, if it has any.
+fn check_config_bindings(report: &mut ConfigReport, file: &str, config: &config::Config) {
+    match config.get::<HashMap<String, HashMap<String, config::Value>>>("keybindings") {
+        Ok(raw) => check_bindings(report, file, raw, |event| {
+            event.try_deserialize::<GameEvent>().map(Some).map_err(|e| e.to_string())
+        }),
+        Err(config::ConfigError::NotFound(_)) => {}
+        Err(e) => report.add(file, "keybindings", e.to_string()),
+    }
+}
+
+/// Checks that the input contexts, key sequences and events of key bindings can all be parsed,
+/// before they're deserialized into `KeyBindings`.
+fn check_bindings<V>(
+    report: &mut ConfigReport,
+    file: &str,
+    bindings: HashMap<String, HashMap<String, V>>,
+    event: impl Fn(V) -> Result<Option<GameEvent>, String>,
+) {
+    let mut bindings: Vec<_> = bindings.into_iter().collect();
+    bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (context, keys) in bindings {
+        if let Err(e) = InputContext::try_from(context.clone()) {
+            report.add(file, format!("keybindings.{context}"), e);
+        }
+        let mut keys: Vec<_> = keys.into_iter().collect();
+        keys.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (key, value) in keys {
+            let field = format!("keybindings.{context}.{key}");
+            if let Err(e) = parse_key_sequence(&key) {
+                report.add(file, field.clone(), e);
+            }
+            if let Err(e) = event(value) {
+                report.add(file, field, e);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
-pub struct KeyBindings(pub HashMap<GameState, HashMap<Vec<KeyEvent>, GameEvent>>);
+pub struct KeyBindings(pub HashMap<InputContext, HashMap<Vec<KeyEvent>, GameEvent>>);
 
 impl<'de> Deserialize<'de> for KeyBindings {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let parsed_map = HashMap::<GameState, HashMap<String, GameEvent>>::deserialize(deserializer)?;
+        let parsed_map = HashMap::<InputContext, HashMap<String, GameEvent>>::deserialize(deserializer)?;
 
         let keybindings = parsed_map
             .into_iter()
             .map(|(mode, inner_map)| {
                 let converted_inner_map = inner_map
                     .into_iter()
-                    .map(|(key_str, event)| (parse_key_sequence(&key_str).unwrap(), event))
-                    .collect();
-                (mode, converted_inner_map)
+                    .map(|(key_str, event)| Ok((parse_key_sequence(&key_str).map_err(de::Error::custom)?, event)))
+                    .collect::<Result<_, D::Error>>()?;
+                Ok((mode, converted_inner_map))
             })
-            .collect();
+            .collect::<Result<_, D::Error>>()?;
 
         debug!("parsed keybindings: {keybindings:?}");
         Ok(KeyBindings(keybindings))
     }
 }
 
+/// The key bindings changed in the game, which are saved to the user's config and applied over
+/// the bindings from the config files. `None` unbinds a key which is bound in the config files.
+#[derive(Clone, Debug, Default, PartialEq, Deref, DerefMut)]
+pub struct KeyBindingChanges(pub HashMap<InputContext, HashMap<Vec<KeyEvent>, Option<GameEvent>>>);
+
+impl KeyBindingChanges {
+    /// The changes which turn the `defaults` into the `bindings`.
+    pub fn between(defaults: &KeyBindings, bindings: &KeyBindings) -> Self {
+        let mut changes = Self::default();
+        let empty = HashMap::new();
+        for context in defaults.keys().chain(bindings.keys()) {
+            let (old, new) = (
+                defaults.get(context).unwrap_or(&empty),
+                bindings.get(context).unwrap_or(&empty),
+            );
+            let changed: HashMap<_, _> = new
+                .iter()
+                .filter(|(keys, event)| old.get(*keys) != Some(*event))
+                .map(|(keys, event)| (keys.clone(), Some(event.clone())))
+                .chain(
+                    old.keys()
+                        .filter(|keys| !new.contains_key(*keys))
+                        .map(|keys| (keys.clone(), None)),
+                )
+                .collect();
+            if !changed.is_empty() {
+                changes.insert(context.clone(), changed);
+            }
+        }
+        changes
+    }
+
+    /// Binds and unbinds the changed keys, leaving the other keys of each context as they are.
+    pub fn apply(&self, bindings: &mut KeyBindings) {
+        for (context, changes) in self.iter() {
+            let context_bindings = bindings.entry(context.clone()).or_default();
+            for (keys, event) in changes {
+                match event {
+                    Some(event) => context_bindings.insert(keys.clone(), event.clone()),
+                    None => context_bindings.remove(keys),
+                };
+            }
+        }
+    }
+
+    /// Writes the changes to a JSON file, in the format of the `keybindings` section of the UI
+    /// config with `null` for unbound keys.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBindingChanges {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parsed_map = HashMap::<InputContext, HashMap<String, Option<GameEvent>>>::deserialize(deserializer)?;
+        let changes = parsed_map
+            .into_iter()
+            .map(|(context, changes)| {
+                let changes = changes
+                    .into_iter()
+                    .map(|(key_str, event)| Ok((parse_key_sequence(&key_str).map_err(de::Error::custom)?, event)))
+                    .collect::<Result<_, D::Error>>()?;
+                Ok((context, changes))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        Ok(KeyBindingChanges(changes))
+    }
+}
+
+impl Serialize for KeyBindingChanges {
+    /// Sorted, so saved files are stable.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let sorted: BTreeMap<String, BTreeMap<String, &Option<GameEvent>>> = self
+            .iter()
+            .map(|(context, changes)| {
+                let changes = changes
+                    .iter()
+                    .map(|(keys, event)| (key_sequence_to_string(keys), event))
+                    .collect();
+                (context.to_string(), changes)
+            })
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
+
 fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
@@ -180,7 +529,7 @@ fn parse_key_code_with_modifiers(raw: &str, mut modifiers: KeyModifiers) -> Resu
             }
             KeyCode::Char(c)
         }
-        _ => return Err(format!("Unable to parse {raw}")),
+        _ => return Err(format!("unknown key `{raw}`")),
     };
     Ok(KeyEvent::new(c, modifiers))
 }
@@ -204,10 +553,16 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
         KeyCode::Delete => "delete",
         KeyCode::Insert => "insert",
         KeyCode::F(c) => {
-            char = format!("f({c})");
+            char = format!("f{c}");
             &char
         }
         KeyCode::Char(c) if c == ' ' => "space",
+        KeyCode::Char('-') => "minus",
+        // Written the way they're configured, `shift-a` rather than `shift-A`
+        KeyCode::Char(c) if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+            char = c.to_lowercase().to_string();
+            &char
+        }
         KeyCode::Char(c) => {
             char = c.to_string();
             &char
@@ -249,14 +604,18 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
     key
 }
 
+/// Writes a key sequence the way it's configured, e.g. `<shift-z><shift-z>`.
+pub fn key_sequence_to_string(keys: &[KeyEvent]) -> String {
+    keys.iter().map(|k| format!("<{}>", key_event_to_string(k))).collect()
+}
+
 pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
     if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
-        return Err(format!("Unable to parse `{}`", raw));
+        return Err(format!("unbalanced `<` and `>` in `{raw}`"));
     }
     let raw = if !raw.contains("><") {
         let raw = raw.strip_prefix('<').unwrap_or(raw);
-        let raw = raw.strip_prefix('>').unwrap_or(raw);
-        raw
+        raw.strip_prefix('>').unwrap_or(raw)
     } else {
         raw
     };
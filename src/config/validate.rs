@@ -0,0 +1,305 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{effect::Effect, rng};
+
+use super::{Config, Loot};
+
+/// Something wrong with a config or data file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Where the file was read from.
+    pub file: String,
+    /// Path to the field in the file, e.g. `equipment.carried[2]`. Empty for the file as a whole.
+    pub field: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.file, self.reason)
+        } else {
+            write!(f, "{}: {}: {}", self.file, self.field, self.reason)
+        }
+    }
+}
+
+/// The problems found while loading the config, collected so they can all be fixed in one go.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigReport {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl ConfigReport {
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn add(&mut self, file: &str, field: impl Into<String>, reason: impl Into<String>) {
+        self.problems.push(ConfigProblem {
+            file: file.to_string(),
+            field: field.into(),
+            reason: reason.into(),
+        });
+    }
+
+    /// Adds a problem if the dice string can't be rolled.
+    pub fn dice(&mut self, file: &str, field: impl Into<String>, dice: &str) {
+        if let Err(reason) = rng::parse_dice(dice) {
+            self.add(file, field, reason);
+        }
+    }
+
+    pub fn extend(&mut self, other: ConfigReport) {
+        self.problems.extend(other.problems);
+    }
+
+    /// Ok if nothing was found, otherwise the report itself as the error.
+    pub fn into_result(self) -> Result<(), ConfigReport> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.problems.len();
+        writeln!(
+            f,
+            "Found {count} problem{} in the configuration:",
+            if count == 1 { "" } else { "s" }
+        )?;
+        for problem in &self.problems {
+            writeln!(f, "  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigReport {}
+
+/// Checks what parsing can't: dice strings, sizes and names referring to other files. `files` maps
+/// the file names to where they were read from.
+pub fn validate_config(config: &Config, files: &HashMap<&str, String>, report: &mut ConfigReport) {
+    let file = |name: &str| files.get(name).cloned().unwrap_or_else(|| name.to_string());
+
+    let game = file("game.ron");
+    if config.game.name.trim().is_empty() {
+        report.add(&game, "name", "must not be empty");
+    }
+
+    let player_file = file("player.ron");
+    let player = &config.player;
+    for (field, dice) in [
+        ("str", &player.str),
+        ("con", &player.con),
+        ("int", &player.int),
+        ("dex", &player.dex),
+    ] {
+        report.dice(&player_file, field, dice);
+    }
+    if player.hp <= 0 {
+        report.add(&player_file, "hp", format!("must be above 0, not {}", player.hp));
+    }
+    if player.vision_range < 0 {
+        report.add(
+            &player_file,
+            "vision_range",
+            format!("must not be negative, not {}", player.vision_range),
+        );
+    }
+    let equipment = &player.equipment;
+    let names = equipment
+        .equipped
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (format!("equipment.equipped[{i}]"), n));
+    let names = names.chain(
+        equipment
+            .carried
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (format!("equipment.carried[{i}]"), n)),
+    );
+    for (field, name) in names {
+        if config.items.get(name).is_none() {
+            report.add(&player_file, field, format!("unknown item `{name}`"));
+        }
+    }
+    for (i, stack) in equipment.carried_stacks.iter().enumerate() {
+        if config.items.get(&stack.name).is_none() {
+            report.add(
+                &player_file,
+                format!("equipment.carried_stacks[{i}].name"),
+                format!("unknown item `{}`", stack.name),
+            );
+        }
+        if stack.amount <= 0 {
+            report.add(
+                &player_file,
+                format!("equipment.carried_stacks[{i}].amount"),
+                format!("must be above 0, not {}", stack.amount),
+            );
+        }
+    }
+    for (i, spell) in player.spells.iter().enumerate() {
+        if config.spells.id(spell).is_none() {
+            report.add(&player_file, format!("spells[{i}]"), format!("unknown spell `{spell}`"));
+        }
+    }
+
+    let world = file("world.ron");
+    for (field, value) in [
+        ("max_levels", config.world.max_levels),
+        ("default_map_width", config.world.default_map_width),
+        ("default_map_height", config.world.default_map_height),
+    ] {
+        if value <= 0 {
+            report.add(&world, field, format!("must be above 0, not {value}"));
+        }
+    }
+
+    let experience = file("experience.ron");
+    report.dice(&experience, "hp_per_level", &config.experience.hp_per_level);
+    report.dice(&experience, "mp_per_level", &config.experience.mp_per_level);
+    report.dice(&experience, "attribute_gain", &config.experience.attribute_gain);
+
+    let items = file("items.ron");
+    for item in &config.items.items {
+        let field = format!("items[\"{}\"]", item.name);
+        effects(report, &items, &format!("{field}.effects"), &item.effects);
+        if let Some(throw) = &item.throw {
+            report.dice(&items, format!("{field}.throw.damage"), &throw.damage);
+            effects(report, &items, &format!("{field}.throw.effects"), &throw.effects);
+        }
+        if let Some(equipment) = &item.equipment {
+            if let Some(damage) = &equipment.damage {
+                report.dice(&items, format!("{field}.equipment.damage"), damage);
+            }
+            if let Some(digging) = &equipment.digging {
+                report.dice(&items, format!("{field}.equipment.digging"), digging);
+            }
+            if let Some(launcher) = &equipment.launcher {
+                report.dice(&items, format!("{field}.equipment.launcher.damage"), &launcher.damage);
+                if config.items.get(&launcher.ammo).is_none() {
+                    report.add(
+                        &items,
+                        format!("{field}.equipment.launcher.ammo"),
+                        format!("unknown item `{}`", launcher.ammo),
+                    );
+                }
+            }
+        }
+    }
+
+    let spells = file("spells.ron");
+    for spell in &config.spells.spells {
+        effects(
+            report,
+            &spells,
+            &format!("spells[\"{}\"].effects", spell.name),
+            &spell.effects,
+        );
+    }
+
+    let loot = file("loot.ron");
+    let mut tables: Vec<_> = config.loot.tables.iter().collect();
+    tables.sort_by_key(|(name, _)| *name);
+    for (table, entries) in tables {
+        for (i, entry) in entries.iter().enumerate() {
+            let field = format!("tables[\"{table}\"][{i}]");
+            match &entry.loot {
+                Loot::Item(name) if config.items.get(name).is_none() => {
+                    report.add(&loot, format!("{field}.loot"), format!("unknown item `{name}`"));
+                }
+                Loot::Table(name) if !config.loot.tables.contains_key(name) => {
+                    report.add(&loot, format!("{field}.loot"), format!("unknown table `{name}`"));
+                }
+                _ => {}
+            }
+            if let Some(count) = &entry.count {
+                report.dice(&loot, format!("{field}.count"), count);
+            }
+        }
+    }
+    for (i, level) in config.loot.levels.iter().enumerate() {
+        if !config.loot.tables.contains_key(&level.table) {
+            report.add(
+                &loot,
+                format!("levels[{i}].table"),
+                format!("unknown table `{}`", level.table),
+            );
+        }
+        report.dice(&loot, format!("levels[{i}].items"), &level.items);
+    }
+    if let Some(key) = &config.loot.key
+        && config.items.get(key).is_none()
+    {
+        report.add(&loot, "key", format!("unknown item `{key}`"));
+    }
+}
+
+fn effects(report: &mut ConfigReport, file: &str, field: &str, effects: &[Effect]) {
+    for (i, effect) in effects.iter().enumerate() {
+        match effect {
+            Effect::Damage(dice) | Effect::Demolish(dice) | Effect::Heal(dice) => {
+                report.dice(file, format!("{field}[{i}]"), dice)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ExperienceConfig, GameConfig, ItemsConfig, LootConfig, PlayerConfig, SpellsConfig, TilesConfig, WorldConfig,
+    };
+
+    fn config() -> Config {
+        Config {
+            game: GameConfig::new(),
+            player: PlayerConfig {
+                hp: 10,
+                ..PlayerConfig::new()
+            },
+            world: WorldConfig::new(),
+            experience: ExperienceConfig::new(),
+            spells: SpellsConfig::new(),
+            items: ItemsConfig::new(),
+            loot: LootConfig::new(),
+            tiles: TilesConfig::new(),
+        }
+    }
+
+    #[test]
+    fn all_problems_are_collected_with_their_fields() {
+        let mut config = config();
+        config.player.str = "5d10+5x".to_string();
+        config.player.equipment.carried.push("no such thing".to_string());
+        config.player.spells.push("no such spell".to_string());
+        config.world.default_map_width = 0;
+        let files = HashMap::from([("player.ron", "config/player.ron".to_string())]);
+
+        let mut report = ConfigReport::default();
+        validate_config(&config, &files, &mut report);
+        let problems: Vec<String> = report.problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            vec![
+                "config/player.ron: str: `5d10+5x` is not a dice string like `3d6+2`",
+                "config/player.ron: equipment.carried[0]: unknown item `no such thing`",
+                "config/player.ron: spells[0]: unknown spell `no such spell`",
+                "world.ron: default_map_width: must be above 0, not 0",
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let mut report = ConfigReport::default();
+        validate_config(&config(), &HashMap::new(), &mut report);
+        assert_eq!(report.into_result(), Ok(()));
+    }
+}
@@ -0,0 +1,112 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    component::Position,
+    map::{Map, field_of_view},
+    status::StatusKind,
+    targeting::line_of_fire,
+};
+
+/// Something that happens to whatever is on a tile, caused by a spell or an item.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Effect {
+    /// Damage, as a dice string.
+    Damage(String),
+    /// Damage to the tile itself, as a dice string. Walls and doors which take enough of it are
+    /// destroyed.
+    Demolish(String),
+    /// Restores hit points, as a dice string.
+    Heal(String),
+    /// Applies a status effect for the given number of ticks.
+    Status(StatusKind, u64),
+    /// Moves the source of the effect to the tile.
+    Teleport,
+    /// Reveals the whole map the tile is on.
+    RevealMap,
+    /// Identifies one of the unknown items carried by whoever is on the tile, and reveals which of
+    /// them are blessed or cursed.
+    Identify,
+    /// Removes curses from the items equipped by whoever is on the tile, and reveals which of the
+    /// items they carry are blessed or cursed.
+    RemoveCurse,
+}
+
+/// Which tiles an effect reaches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Area {
+    /// Only the one causing the effect, no targeting needed.
+    #[default]
+    Caster,
+    /// A single targeted tile.
+    Target,
+    /// All tiles within the radius of the targeted tile which can be seen from it.
+    Radius(i32),
+}
+
+impl Area {
+    pub fn needs_target(&self) -> bool {
+        !matches!(self, Area::Caster)
+    }
+
+    /// Returns the tiles affected when the effect originates at `caster` and is aimed at `target`.
+    pub fn tiles(&self, map: &Map, caster: (i32, i32), target: (i32, i32)) -> Vec<(i32, i32)> {
+        match self {
+            Area::Caster => vec![caster],
+            Area::Target => vec![target],
+            Area::Radius(radius) => field_of_view(map, target.0, target.1, *radius),
+        }
+    }
+
+    /// Like `tiles`, but for effects which travel along the line of fire from the caster towards
+    /// the target, and take place where they hit something.
+    pub fn affected_tiles(
+        &self,
+        map: &Map,
+        caster: (i32, i32),
+        target: (i32, i32),
+        occupied: impl Fn(i32, i32) -> bool,
+    ) -> Vec<(i32, i32)> {
+        if !self.needs_target() {
+            return self.tiles(map, caster, caster);
+        }
+        let impact = line_of_fire(map, caster, target, occupied).last().copied().unwrap_or(caster);
+        self.tiles(map, caster, impact)
+    }
+}
+
+/// Asks for an effect to be applied at a position. Handled by `effect_system`.
+#[derive(Event, Debug, Clone)]
+pub struct EffectEvent {
+    /// The entity causing the effect. Gets the experience if the effect kills something.
+    pub source: Entity,
+    pub effect: Effect,
+    pub pos: Position,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+
+    #[test]
+    fn single_tile_areas() {
+        let map = Map::new(1, "test", 10, 10);
+        assert_eq!(Area::Caster.tiles(&map, (1, 1), (5, 5)), vec![(1, 1)]);
+        assert_eq!(Area::Target.tiles(&map, (1, 1), (5, 5)), vec![(5, 5)]);
+        assert!(!Area::Caster.needs_target());
+        assert!(Area::Radius(2).needs_target());
+    }
+
+    #[test]
+    fn targeted_areas_stop_at_the_first_occupant() {
+        let mut map = Map::new(1, "test", 10, 10);
+        for x in 0..10 {
+            map.define_tile(x, 1, TileType::Floor);
+        }
+        let tiles = Area::Target.affected_tiles(&map, (1, 1), (8, 1), |x, _| x == 4);
+        assert_eq!(tiles, vec![(4, 1)]);
+        let tiles = Area::Caster.affected_tiles(&map, (1, 1), (8, 1), |_, _| false);
+        assert_eq!(tiles, vec![(1, 1)]);
+    }
+}
@@ -13,6 +13,17 @@ pub struct PlayerMoveRelativeEvent {
 #[derive(Event, Debug, Clone, PartialEq, Eq)]
 pub struct PlayerSpentEnergy(pub i32);
 
+/// Sent when tiles of a map change, e.g. a door is opened or a wall destroyed, so whatever
+/// depends on them can be updated.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MapChanged {
+    pub map: usize,
+}
+
+/// Sent once for every game tick that passes. Holds the number of the tick.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct GameTick(pub u64);
+
 #[allow(dead_code)]
 #[derive(Event, Debug, Clone, PartialEq, Eq)]
 pub enum EntityAction {
@@ -16,6 +16,9 @@ use bevy_ecs::{entity::Entity, resource::Resource};
 
 use crate::{component::Position, map::Maps};
 
+/// Amount of energy the player must spend for one game tick to pass.
+pub const TICK_ENERGY: i32 = 100;
+
 // TODO: this could potentially be stored as an Entity
 #[derive(Resource, Default)]
 pub struct CurrentGameData {
@@ -25,6 +28,28 @@ pub struct CurrentGameData {
     pub maps: Maps,
 }
 
+/// Keeps track of how much game time has passed.
+/// Game time is measured in ticks, and only advances when the player spends energy.
+#[derive(Resource, Default)]
+pub struct GameTime {
+    pub tick: u64,
+    energy: i32,
+}
+
+impl GameTime {
+    /// Adds spent energy to the clock, and returns the ticks which elapsed because of it.
+    pub fn advance(&mut self, energy: i32) -> Vec<u64> {
+        let mut elapsed = Vec::new();
+        self.energy += energy;
+        while self.energy >= TICK_ENERGY {
+            self.energy -= TICK_ENERGY;
+            self.tick += 1;
+            elapsed.push(self.tick);
+        }
+        elapsed
+    }
+}
+
 /*
 pub struct Game {
     pub world: World,
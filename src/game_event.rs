@@ -1,4 +1,4 @@
-use bevy_ecs::event::Event;
+use bevy_ecs::{entity::Entity, event::Event};
 use serde::{
     // de::{self, Deserializer, Visitor},
     Deserialize,
@@ -6,6 +6,8 @@ use serde::{
 };
 use strum::Display;
 
+use crate::{component::Skill, item::ItemAction, map::DoorAction, targeting::TargetingPurpose};
+
 #[derive(Event, Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum GameEvent {
     Quit,
@@ -15,11 +17,110 @@ pub enum GameEvent {
     NextMenuItem,
     PrevMenuItem,
     SelectMenuItem,
-    PlayerMoveAbsolute { x: i32, y: i32 },
-    PlayerMoveRelative { dx: i32, dy: i32 },
+    PlayerMoveAbsolute {
+        x: i32,
+        y: i32,
+    },
+    PlayerMoveRelative {
+        dx: i32,
+        dy: i32,
+    },
+    PlayerWait,
+    PlayerRest,
+    PlayerAutoExplore,
+    ShowLevelUp,
+    ReturnToGame,
+    AllocateSkillPoint(Skill),
+    // Events which refer to entities are only sent by the game itself, never bound to keys.
+    #[serde(skip)]
+    StartTargeting(TargetingPurpose),
+    MoveTargetCursor {
+        dx: i32,
+        dy: i32,
+    },
+    NextTarget,
+    PrevTarget,
+    ConfirmTarget,
+    #[serde(skip)]
+    PlayerThrow {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    /// Fires the wielded launcher, after choosing a target.
+    FireWeapon,
+    #[serde(skip)]
+    PlayerFire {
+        weapon: Entity,
+        ammo: Entity,
+        x: i32,
+        y: i32,
+    },
+    ShowSpellMenu,
+    SelectSpell(usize),
+    PlayerCast {
+        spell: usize,
+        x: i32,
+        y: i32,
+    },
+    ShowItemMenu(ItemAction),
+    #[serde(skip)]
+    ChooseItemAction {
+        item: Entity,
+        action: ItemAction,
+    },
+    #[serde(skip)]
+    PlayerUseItem {
+        item: Entity,
+        x: i32,
+        y: i32,
+    },
+    #[serde(skip)]
+    PlayerEquip(Entity),
+    #[serde(skip)]
+    PlayerUnequip(Entity),
+    #[serde(skip)]
+    PlayerDrop(Entity),
+    /// Picks up the items lying where the player stands.
+    PlayerPickUp,
+    ShowInventory,
+    ShowEquipment,
+    /// Picks the entry with the given letter in a lettered list.
+    SelectLetter(char),
+    #[serde(skip)]
+    ShowItemDetail(Entity),
+    /// Does something with the item shown in the item details.
+    ItemContextAction(ItemAction),
+    CloseItemDetail,
+    /// Opens or closes a door next to the player, asking for the direction if there's more than one.
+    OperateDoor(DoorAction),
+    ChooseDirection {
+        dx: i32,
+        dy: i32,
+    },
+    PlayerOperateDoor {
+        action: DoorAction,
+        x: i32,
+        y: i32,
+    },
+    /// Bashes (or digs, with a digging tool) into a wall or door next to the player, asking for the direction.
+    Bash,
+    PlayerBash {
+        x: i32,
+        y: i32,
+    },
     // Suspend,
     // Tick,
     // Render,
-    // Resize(u16, u16),
+    /// Opens the key binding editor.
+    ShowKeyBindings,
+    /// Removes the selected key binding in the key binding editor.
+    UnbindKey,
+    SaveKeyBindings,
+    /// Sends the next key pressed as a `CapturedKey`, instead of looking up its binding.
+    StartKeyCapture,
+    CapturedKey(String),
+    /// The terminal was resized to the given width and height.
+    Resize(u16, u16),
     // Error(String),
 }
@@ -0,0 +1,47 @@
+use bevy::{ecs::name::Name, log::info};
+use bevy_ecs::resource::Resource;
+
+/// The message log shown to the player.
+#[derive(Resource, Default)]
+pub struct GameLog {
+    pub entries: Vec<String>,
+}
+
+impl GameLog {
+    pub fn add<T: ToString>(&mut self, message: T) {
+        let message = message.to_string();
+        info!("Game log: {}", message);
+        self.entries.push(message);
+    }
+
+    /// Returns the `n` most recent entries, oldest first.
+    pub fn latest(&self, n: usize) -> Vec<String> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+}
+
+/// How an entity is referred to in messages.
+pub fn display_name(name: Option<&Name>, player: bool) -> String {
+    if player {
+        "you".to_string()
+    } else {
+        name.map_or("something".to_string(), |n| n.to_string())
+    }
+}
+
+/// Conjugates a verb for the player ("you hit") or someone else ("it hits").
+pub fn verb(verb: &str, player: bool) -> String {
+    if player { verb.to_string() } else { format!("{}s", verb) }
+}
+
+pub fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,4 +1,13 @@
-#[derive(Debug, PartialEq, Eq)]
+use bevy::log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::rng;
+
+/// How much each skill point adds to the effective value of a check.
+pub const SKILL_POINT_VALUE: i32 = 5;
+
+/// The degree of a success or failure. Variants are ordered from least to most extreme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RollResultType {
     Normal,
     Great,
@@ -6,13 +15,263 @@ pub enum RollResultType {
     Critical,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RollResult {
     Success(RollResultType),
     Failure(RollResultType),
 }
 
+impl RollResult {
+    pub fn is_success(&self) -> bool {
+        matches!(self, RollResult::Success(_))
+    }
+
+    /// A signed rank used to compare results: 1 to 4 for successes, -1 to -4 for failures.
+    pub fn rank(&self) -> i32 {
+        match self {
+            RollResult::Success(degree) => *degree as i32 + 1,
+            RollResult::Failure(degree) => -(*degree as i32 + 1),
+        }
+    }
+}
+
+/// How hard a check is. Applied as a modifier to the effective value.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Trivial,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    VeryHard,
+    Impossible,
+}
+
+impl Difficulty {
+    pub fn modifier(&self) -> i32 {
+        match self {
+            Difficulty::Trivial => 40,
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 0,
+            Difficulty::Hard => -20,
+            Difficulty::VeryHard => -40,
+            Difficulty::Impossible => -60,
+        }
+    }
+}
+
+/// Advantage rolls twice and keeps the better roll, disadvantage keeps the worse.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RollMode {
+    #[default]
+    Normal,
+    Advantage,
+    Disadvantage,
+}
+
+impl RollMode {
+    /// Picks which of two d100 rolls counts. Lower rolls are better.
+    pub fn pick(&self, first: i32, second: i32) -> i32 {
+        match self {
+            RollMode::Normal => first,
+            RollMode::Advantage => first.min(second),
+            RollMode::Disadvantage => first.max(second),
+        }
+    }
+}
+
+/// Everything besides the rolled value itself which affects a single check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CheckModifiers {
+    /// Skill points, each worth `SKILL_POINT_VALUE`.
+    pub skill: i32,
+    pub difficulty: Difficulty,
+    pub mode: RollMode,
+}
+
+impl CheckModifiers {
+    pub fn skill(skill: i32) -> Self {
+        Self {
+            skill,
+            ..Default::default()
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: RollMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
 pub trait Rollable {
-    fn check(&self, roll: i32) -> RollResult;
-    fn roll(&self) -> RollResult;
+    /// The value a d100 roll must be equal to or lower than to succeed, before check modifiers.
+    fn target(&self) -> i32;
+
+    /// The value rolled against once skill and difficulty are taken into account.
+    fn effective_value(&self, modifiers: &CheckModifiers) -> i32 {
+        self.target() + modifiers.skill * SKILL_POINT_VALUE + modifiers.difficulty.modifier()
+    }
+
+    fn check(&self, roll: i32) -> RollResult {
+        check_target(self.target(), roll)
+    }
+
+    fn roll(&self) -> RollResult {
+        self.roll_with(&CheckModifiers::default())
+    }
+
+    fn roll_with(&self, modifiers: &CheckModifiers) -> RollResult {
+        let first = rng::roll_str("1d100");
+        let roll = match modifiers.mode {
+            RollMode::Normal => first,
+            _ => modifiers.mode.pick(first, rng::roll_str("1d100")),
+        };
+        check_target(self.effective_value(modifiers), roll)
+    }
+}
+
+/// Resolves a d100 roll against a target value.
+///
+/// A roll of 1 is always a critical success and a roll of 100 always a critical failure.
+pub fn check_target(target: i32, roll: i32) -> RollResult {
+    let great_success = (target as f32 * 0.4).max(3.0) as i32;
+    let extreme_success = (target as f32 * 0.1).max(2.0) as i32;
+    let great_failure = ((100 - target) as f32 * 0.4).max(2.0) as i32;
+    let extreme_failure = ((100 - target) as f32 * 0.1).max(1.0) as i32;
+
+    let result = if roll == 1 {
+        RollResult::Success(RollResultType::Critical)
+    } else if roll == 100 {
+        RollResult::Failure(RollResultType::Critical)
+    } else if roll <= target {
+        if roll <= extreme_success {
+            RollResult::Success(RollResultType::Extreme)
+        } else if roll <= great_success {
+            RollResult::Success(RollResultType::Great)
+        } else {
+            RollResult::Success(RollResultType::Normal)
+        }
+    } else if (100 - roll) < extreme_failure {
+        RollResult::Failure(RollResultType::Extreme)
+    } else if (100 - roll) < great_failure {
+        RollResult::Failure(RollResultType::Great)
+    } else {
+        RollResult::Failure(RollResultType::Normal)
+    };
+
+    debug!("rolled {} against {}: {:?}", roll, target, result);
+    result
+}
+
+/// Which side of an opposed roll came out on top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winner {
+    Attacker,
+    Defender,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpposedResult {
+    pub winner: Winner,
+    pub attacker: RollResult,
+    pub defender: RollResult,
+}
+
+/// Rolls a contest between two rollables.
+pub fn opposed_roll(
+    attacker: &impl Rollable,
+    attacker_modifiers: &CheckModifiers,
+    defender: &impl Rollable,
+    defender_modifiers: &CheckModifiers,
+) -> OpposedResult {
+    let attacker_value = attacker.effective_value(attacker_modifiers);
+    let defender_value = defender.effective_value(defender_modifiers);
+    let result = compare(
+        attacker.roll_with(attacker_modifiers),
+        attacker_value,
+        defender.roll_with(defender_modifiers),
+        defender_value,
+    );
+    debug!("opposed roll {} vs {}: {:?}", attacker_value, defender_value, result);
+    result
+}
+
+/// Decides an opposed roll. The attacker has to succeed and beat the defender's degree of
+/// success; equal degrees go to the higher effective value, and remaining ties to the defender.
+pub fn compare(attacker: RollResult, attacker_value: i32, defender: RollResult, defender_value: i32) -> OpposedResult {
+    let attacker_wins = attacker.is_success()
+        && (attacker.rank() > defender.rank() || (attacker.rank() == defender.rank() && attacker_value > defender_value));
+
+    OpposedResult {
+        winner: if attacker_wins { Winner::Attacker } else { Winner::Defender },
+        attacker,
+        defender,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degree_boundaries() {
+        // target 50: extreme <= 5, great <= 20, great failure above 80, extreme failure above 95
+        assert_eq!(check_target(50, 1), RollResult::Success(RollResultType::Critical));
+        assert_eq!(check_target(50, 5), RollResult::Success(RollResultType::Extreme));
+        assert_eq!(check_target(50, 6), RollResult::Success(RollResultType::Great));
+        assert_eq!(check_target(50, 20), RollResult::Success(RollResultType::Great));
+        assert_eq!(check_target(50, 21), RollResult::Success(RollResultType::Normal));
+        assert_eq!(check_target(50, 50), RollResult::Success(RollResultType::Normal));
+        assert_eq!(check_target(50, 51), RollResult::Failure(RollResultType::Normal));
+        assert_eq!(check_target(50, 80), RollResult::Failure(RollResultType::Normal));
+        assert_eq!(check_target(50, 81), RollResult::Failure(RollResultType::Great));
+        assert_eq!(check_target(50, 95), RollResult::Failure(RollResultType::Great));
+        assert_eq!(check_target(50, 96), RollResult::Failure(RollResultType::Extreme));
+        assert_eq!(check_target(50, 99), RollResult::Failure(RollResultType::Extreme));
+        assert_eq!(check_target(50, 100), RollResult::Failure(RollResultType::Critical));
+    }
+
+    #[test]
+    fn naturals_ignore_target() {
+        assert_eq!(check_target(-20, 1), RollResult::Success(RollResultType::Critical));
+        assert_eq!(check_target(150, 100), RollResult::Failure(RollResultType::Critical));
+        assert_eq!(check_target(150, 99), RollResult::Success(RollResultType::Normal));
+    }
+
+    #[test]
+    fn results_are_ranked() {
+        assert!(RollResultType::Critical > RollResultType::Normal);
+        assert_eq!(RollResult::Success(RollResultType::Critical).rank(), 4);
+        assert_eq!(RollResult::Success(RollResultType::Normal).rank(), 1);
+        assert_eq!(RollResult::Failure(RollResultType::Normal).rank(), -1);
+        assert_eq!(RollResult::Failure(RollResultType::Critical).rank(), -4);
+    }
+
+    #[test]
+    fn advantage_picks_the_better_roll() {
+        assert_eq!(RollMode::Normal.pick(70, 10), 70);
+        assert_eq!(RollMode::Advantage.pick(70, 10), 10);
+        assert_eq!(RollMode::Disadvantage.pick(70, 10), 70);
+    }
+
+    #[test]
+    fn opposed_rolls_compare_degrees() {
+        let great = RollResult::Success(RollResultType::Great);
+        let normal = RollResult::Success(RollResultType::Normal);
+        let failure = RollResult::Failure(RollResultType::Normal);
+
+        assert_eq!(compare(great, 10, normal, 90).winner, Winner::Attacker);
+        assert_eq!(compare(normal, 90, great, 10).winner, Winner::Defender);
+        assert_eq!(compare(normal, 60, normal, 50).winner, Winner::Attacker);
+        assert_eq!(compare(normal, 50, normal, 50).winner, Winner::Defender);
+        assert_eq!(compare(failure, 90, failure, 10).winner, Winner::Defender);
+        assert_eq!(compare(normal, 10, failure, 90).winner, Winner::Attacker);
+    }
 }
@@ -0,0 +1,507 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::log::warn;
+use bevy_ecs::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CFG,
+    component::{DetailedName, Position, Render},
+    config::{BucChances, ItemClass, ItemDefinition, ItemsConfig},
+    rng,
+};
+
+/// An item. The name refers to its definition in the item data file.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub name: String,
+}
+
+impl Item {
+    pub fn definition(&self) -> Option<ItemDefinition> {
+        CFG.lock().unwrap().config.items.get(&self.name).cloned()
+    }
+}
+
+/// What the player wants to do with an item picked from the item menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemAction {
+    Use,
+    Throw,
+    Equip,
+    Remove,
+    Drop,
+}
+
+impl ItemAction {
+    pub fn title(&self) -> &'static str {
+        match self {
+            ItemAction::Use => "Use which item?",
+            ItemAction::Throw => "Throw which item?",
+            ItemAction::Equip => "Wear or wield which item?",
+            ItemAction::Remove => "Take off which item?",
+            ItemAction::Drop => "Drop which item?",
+        }
+    }
+
+    /// Short description of the action, for lists of actions.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemAction::Use => "use",
+            ItemAction::Throw => "throw",
+            ItemAction::Equip => "put on",
+            ItemAction::Remove => "take off",
+            ItemAction::Drop => "drop",
+        }
+    }
+
+    /// Whether an item can be picked for this action.
+    pub fn allows(&self, definition: &ItemDefinition, equipped: bool) -> bool {
+        match self {
+            ItemAction::Use => definition.usable(),
+            ItemAction::Throw => definition.throw.is_some() && !equipped,
+            ItemAction::Equip => definition.equippable() && !equipped,
+            ItemAction::Remove => equipped,
+            ItemAction::Drop => true,
+        }
+    }
+}
+
+/// Number of identical items in a stack.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Stack {
+    pub count: i32,
+}
+
+/// Remaining uses of an item with charges.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Charges {
+    pub current: i32,
+}
+
+/// Marks an item as worn or wielded by the entity carrying it.
+#[derive(Component, Default, Serialize, Deserialize, Debug)]
+pub struct Equipped;
+
+/// Whether an item is blessed, uncursed or cursed. Cursed items can't be taken off once they are
+/// equipped, and weaken whoever wears them.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Buc {
+    Blessed,
+    #[default]
+    Uncursed,
+    Cursed,
+}
+
+impl Buc {
+    pub fn roll(chances: &BucChances) -> Buc {
+        let roll = rng::range(0, 100);
+        if roll < chances.blessed {
+            Buc::Blessed
+        } else if roll < chances.blessed + chances.cursed {
+            Buc::Cursed
+        } else {
+            Buc::Uncursed
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Buc::Blessed => "blessed",
+            Buc::Uncursed => "uncursed",
+            Buc::Cursed => "cursed",
+        }
+    }
+}
+
+/// Marks items whose `Buc` state the player knows.
+#[derive(Component, Default, Serialize, Deserialize, Debug)]
+pub struct BucKnown;
+
+/// Attribute penalty for every cursed item an entity has equipped.
+pub const CURSE_PENALTY: i32 = 5;
+
+/// The penalty currently subtracted from an entity's attribute modifiers because of cursed
+/// equipment. Kept up to date by `curse_penalty_system`.
+#[derive(Component, Default, Serialize, Deserialize, Debug)]
+pub struct CursePenalty(pub i32);
+
+/// The entity carrying an item.
+// Entity ids aren't stable between runs, so this can't be saved.
+#[derive(Component, Debug)]
+#[relationship(relationship_target = Inventory)]
+pub struct CarriedBy(pub Entity);
+
+/// The items an entity carries. Maintained by bevy from `CarriedBy`.
+#[derive(Component, Debug)]
+#[relationship_target(relationship = CarriedBy, linked_spawn)]
+pub struct Inventory(Vec<Entity>);
+
+/// The item kinds the player has identified, and the names unidentified kinds are shown with.
+/// The names are shuffled from the appearances in the item data, differently for every game.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KnownItems {
+    /// Name an unidentified item is shown with, by item name.
+    appearances: HashMap<String, String>,
+    /// Names of the items which have been identified.
+    known: HashSet<String>,
+}
+
+impl KnownItems {
+    pub fn new(seed: u64, items: &ItemsConfig) -> Self {
+        // A separate generator, so the appearances don't depend on how much the game rng was used.
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        let mut appearances = HashMap::new();
+        for class in [ItemClass::Potion, ItemClass::Scroll, ItemClass::Wand] {
+            let mut names = items.appearances.get(&class).cloned().unwrap_or_default();
+            for i in (1..names.len()).rev() {
+                let j = rng.range(0, i as i32 + 1) as usize;
+                names.swap(i, j);
+            }
+            let mut names = names.into_iter();
+            for item in items.items.iter().filter(|i| i.class == class) {
+                match names.next() {
+                    Some(appearance) => {
+                        appearances.insert(item.name.clone(), appearance);
+                    }
+                    None => warn!("No appearance left for '{}', it will be known from the start", item.name),
+                }
+            }
+        }
+        Self {
+            appearances,
+            known: HashSet::new(),
+        }
+    }
+
+    pub fn is_known(&self, name: &str) -> bool {
+        !self.appearances.contains_key(name) || self.known.contains(name)
+    }
+
+    /// Marks the item kind as identified. Returns true if it wasn't known before.
+    pub fn identify(&mut self, name: &str) -> bool {
+        !self.is_known(name) && self.known.insert(name.to_string())
+    }
+
+    /// Name the item kind is shown with while it's unidentified.
+    pub fn appearance<'a>(&'a self, name: &'a str) -> &'a str {
+        self.appearances.get(name).map_or(name, |a| a.as_str())
+    }
+
+    /// Name of an item as the player knows it: `full` once it's identified, `base` before that.
+    pub fn shown_name<'a>(&self, item: &Item, name: &'a DetailedName) -> &'a str {
+        if self.is_known(&item.name) { &name.full } else { &name.base }
+    }
+
+    /// Like `item_display_name`, using the name the player knows the item by.
+    /// Charges are only shown for identified items. Pass `buc` only if the player knows it.
+    pub fn display_name(
+        &self,
+        item: &Item,
+        name: &DetailedName,
+        stack: Option<&Stack>,
+        charges: Option<&Charges>,
+        buc: Option<&Buc>,
+    ) -> String {
+        let charges = charges.filter(|_| self.is_known(&item.name));
+        item_display_name(self.shown_name(item, name), stack, charges, buc)
+    }
+}
+
+/// Sent when a monster dies, so it may drop something from the loot table of the level.
+/// Handled by `loot_drop_system`.
+#[derive(Event, Debug, Clone)]
+pub struct LootDropEvent {
+    pub pos: Position,
+}
+
+/// The components of `count` of the named item, and its charges if it has any. Whether it's
+/// blessed or cursed is rolled from the chances in its definition.
+#[allow(clippy::type_complexity)]
+pub fn new_item(
+    name: &str,
+    count: i32,
+    known: Option<&KnownItems>,
+) -> Option<(ItemDefinition, (Item, DetailedName, Stack, Buc), Option<Charges>)> {
+    let Some(definition) = CFG.lock().unwrap().config.items.get(name).cloned() else {
+        warn!("Tried to spawn unknown item '{}'", name);
+        return None;
+    };
+    let base = known
+        .map_or(definition.name.as_str(), |k| k.appearance(&definition.name))
+        .to_string();
+    let components = (
+        Item {
+            name: definition.name.clone(),
+        },
+        DetailedName {
+            base,
+            full: definition.name.clone(),
+        },
+        Stack { count },
+        Buc::roll(&definition.buc),
+    );
+    let charges = definition.charges.map(|current| Charges { current });
+    Some((definition, components, charges))
+}
+
+/// Spawns `count` of the named item into the inventory of `owner`.
+pub fn spawn_carried(world: &mut World, name: &str, count: i32, owner: Entity) -> Option<Entity> {
+    let (_, components, charges) = new_item(name, count, world.get_resource::<KnownItems>())?;
+    let mut item = world.spawn((components, CarriedBy(owner)));
+    if let Some(charges) = charges {
+        item.insert(charges);
+    }
+    Some(item.id())
+}
+
+/// Spawns `count` of the named item lying on the floor at `pos`.
+pub fn spawn_on_floor(world: &mut World, name: &str, count: i32, pos: Position) -> Option<Entity> {
+    let (definition, components, charges) = new_item(name, count, world.get_resource::<KnownItems>())?;
+    let mut item = world.spawn((components, pos, item_render(&definition)));
+    if let Some(charges) = charges {
+        item.insert(charges);
+    }
+    Some(item.id())
+}
+
+/// Gives the player the items listed in the player config.
+/// The equipment the player starts with is uncursed, and known to be.
+pub fn spawn_player_kit(world: &mut World, player: Entity) {
+    let (equipped, carried, stacks) = {
+        let cfg = CFG.lock().unwrap();
+        let equipment = &cfg.config.player.equipment;
+        let stacks: Vec<(String, i32)> = equipment.carried_stacks.iter().map(|s| (s.name.clone(), s.amount)).collect();
+        (equipment.equipped.clone(), equipment.carried.clone(), stacks)
+    };
+
+    for name in equipped {
+        if let Some(item) = spawn_carried(world, &name, 1, player) {
+            world.entity_mut(item).insert((Equipped, Buc::Uncursed, BucKnown));
+        }
+    }
+    for name in carried {
+        spawn_carried(world, &name, 1, player);
+    }
+    for (name, count) in stacks {
+        spawn_carried(world, &name, count, player);
+    }
+}
+
+/// How an item lying on the floor is drawn. Items are drawn below creatures.
+pub fn item_render(definition: &ItemDefinition) -> Render {
+    let fg = match definition.class {
+        ItemClass::Potion => Color::Magenta,
+        ItemClass::Scroll => Color::White,
+        ItemClass::Wand => Color::Cyan,
+        ItemClass::Throwable => Color::Gray,
+        ItemClass::Weapon | ItemClass::Ammo | ItemClass::Armor => Color::LightBlue,
+        ItemClass::Ring | ItemClass::Amulet | ItemClass::Key => Color::Yellow,
+    };
+    Render {
+        glyph: definition.glyph,
+        fg,
+        bg: Color::Black,
+        order: 2,
+        always: false,
+    }
+}
+
+/// Describes what the player knows about an item, one line each, for the item details.
+/// The description and what the item does when used are only shown once it's identified.
+pub fn item_details(definition: &ItemDefinition, identified: bool, buc: Option<Buc>) -> Vec<String> {
+    let mut lines = vec![definition.class.name().to_string()];
+    lines.push(match buc {
+        Some(buc) => format!("It is {}.", buc.name()),
+        None => "You don't know whether it is blessed or cursed.".to_string(),
+    });
+    if !identified {
+        lines.push("You don't know what it does.".to_string());
+    } else if !definition.description.is_empty() {
+        lines.push(definition.description.clone());
+    }
+    if identified && definition.usable() && definition.area.needs_target() {
+        lines.push(format!("Range: {}", definition.range));
+    }
+
+    if let Some(equipment) = &definition.equipment {
+        lines.push(format!("Slot: {:?}", equipment.slot));
+        if let Some(damage) = &equipment.damage {
+            lines.push(format!("Damage: {}  To-hit: {:+}", damage, equipment.to_hit));
+        }
+        if let Some(launcher) = &equipment.launcher {
+            lines.push(format!(
+                "Fires {}s up to {} tiles for {} damage.",
+                launcher.ammo, definition.range, launcher.damage
+            ));
+        }
+        if equipment.dv != 0 || equipment.pv != 0 {
+            lines.push(format!("DV: {:+}  PV: {:+}", equipment.dv, equipment.pv));
+        }
+        let bonus = equipment.attributes;
+        let bonuses: Vec<String> = [("Str", bonus.str), ("Dex", bonus.dex), ("Con", bonus.con), ("Int", bonus.int)]
+            .iter()
+            .filter(|(_, value)| *value != 0)
+            .map(|(name, value)| format!("{} {:+}", name, value))
+            .collect();
+        if !bonuses.is_empty() {
+            lines.push(bonuses.join("  "));
+        }
+    }
+    if let Some(throw) = &definition.throw {
+        lines.push(format!("Thrown, it does {} damage.", throw.damage));
+    }
+    lines
+}
+
+/// Name of an item as shown to the player, including the size of the stack, remaining charges and
+/// whether it's blessed or cursed.
+pub fn item_display_name(name: &str, stack: Option<&Stack>, charges: Option<&Charges>, buc: Option<&Buc>) -> String {
+    let mut name = name.to_string();
+    if let Some(buc) = buc {
+        name = format!("{} {}", buc.name(), name);
+    }
+    if let Some(stack) = stack
+        && stack.count > 1
+    {
+        name = format!("{} {}", stack.count, pluralize(&name));
+    }
+    if let Some(charges) = charges {
+        name = format!("{} ({})", name, charges.current);
+    }
+    name
+}
+
+/// Makes the plural of an item name: "potion of healing" becomes "potions of healing".
+fn pluralize(name: &str) -> String {
+    for separator in [" of ", " labelled "] {
+        if let Some((head, tail)) = name.split_once(separator) {
+            return format!("{}s{}{}", head, separator, tail);
+        }
+    }
+    format!("{}s", name)
+}
+
+/// Uses up one item from the stack, or one charge if the item has charges.
+/// Returns true if the item is gone, in which case it has been despawned.
+pub fn consume(commands: &mut Commands, entity: Entity, stack: Option<Mut<Stack>>, charges: Option<Mut<Charges>>) -> bool {
+    let remaining = if let Some(mut charges) = charges {
+        charges.current -= 1;
+        charges.current
+    } else if let Some(mut stack) = stack {
+        stack.count -= 1;
+        stack.count
+    } else {
+        0
+    };
+    if remaining <= 0 {
+        commands.entity(entity).despawn();
+        return true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_name_shows_stacks_and_charges() {
+        assert_eq!(
+            item_display_name("exploding rock", Some(&Stack { count: 1 }), None, None),
+            "exploding rock"
+        );
+        assert_eq!(
+            item_display_name("exploding rock", Some(&Stack { count: 5 }), None, None),
+            "5 exploding rocks"
+        );
+        assert_eq!(
+            item_display_name("exploding rock", None, Some(&Charges { current: 3 }), None),
+            "exploding rock (3)"
+        );
+        assert_eq!(
+            item_display_name("potion of healing", Some(&Stack { count: 3 }), None, None),
+            "3 potions of healing"
+        );
+        assert_eq!(
+            item_display_name("scroll labelled KIRJE", Some(&Stack { count: 2 }), None, None),
+            "2 scrolls labelled KIRJE"
+        );
+        assert_eq!(
+            item_display_name("potion of healing", Some(&Stack { count: 2 }), None, Some(&Buc::Cursed)),
+            "2 cursed potions of healing"
+        );
+    }
+
+    fn bundled_items() -> ItemsConfig {
+        let contents = std::fs::read_to_string("data/items.ron").unwrap();
+        ron::de::from_str(&contents).unwrap()
+    }
+
+    #[test]
+    fn appearances_are_fixed_by_the_seed() {
+        let items = bundled_items();
+        let first = KnownItems::new(42, &items);
+        assert_eq!(first.appearances, KnownItems::new(42, &items).appearances);
+        let differs = (1..10).any(|seed| KnownItems::new(seed, &items).appearances != first.appearances);
+        assert!(differs);
+
+        let unique: HashSet<&String> = first.appearances.values().collect();
+        assert_eq!(unique.len(), first.appearances.len());
+        assert!(first.appearances.contains_key("potion of healing"));
+        assert!(!first.appearances.contains_key("exploding rock"));
+    }
+
+    #[test]
+    fn identified_items_show_their_full_name() {
+        let mut known = KnownItems::new(7, &bundled_items());
+        let item = Item {
+            name: "wand of fire".to_string(),
+        };
+        let name = DetailedName {
+            base: known.appearance(&item.name).to_string(),
+            full: item.name.clone(),
+        };
+        let charges = Charges { current: 4 };
+        assert!(!known.is_known(&item.name));
+        assert_eq!(known.display_name(&item, &name, None, Some(&charges), None), name.base);
+
+        assert!(known.identify(&item.name));
+        assert!(!known.identify(&item.name));
+        assert_eq!(
+            known.display_name(&item, &name, None, Some(&charges), None),
+            "wand of fire (4)"
+        );
+        assert!(known.is_known("exploding rock"));
+    }
+
+    #[test]
+    fn details_hide_what_unidentified_items_do() {
+        let items = bundled_items();
+        let potion = items.get("potion of healing").unwrap();
+        let unknown = item_details(potion, false, None);
+        assert!(unknown.contains(&"You don't know what it does.".to_string()));
+        assert!(!unknown.contains(&potion.description));
+        let known = item_details(potion, true, Some(Buc::Cursed));
+        assert!(known.contains(&potion.description));
+        assert!(known.contains(&"It is cursed.".to_string()));
+
+        let sword = item_details(items.get("sword of development").unwrap(), true, None);
+        assert!(sword.contains(&"Damage: 1d8  To-hit: +1".to_string()));
+        assert!(sword.contains(&"Str +5".to_string()));
+        let bow = item_details(items.get("short bow").unwrap(), true, None);
+        assert!(bow.contains(&"Fires arrows up to 8 tiles for 1d6 damage.".to_string()));
+    }
+
+    #[test]
+    fn buc_roll_follows_the_chances() {
+        let always_cursed = BucChances { blessed: 0, cursed: 100 };
+        let never = BucChances { blessed: 0, cursed: 0 };
+        for _ in 0..20 {
+            assert_eq!(Buc::roll(&always_cursed), Buc::Cursed);
+            assert_eq!(Buc::roll(&never), Buc::Uncursed);
+        }
+    }
+}
@@ -3,7 +3,7 @@ use bevy::log::*;
 // use bevy::remote::RemotePlugin;
 // use bevy::remote::http::RemoteHttpPlugin;
 use bevy::{app::ScheduleRunnerPlugin, prelude::*, state::app::StatesPlugin};
-use bevy_ratatui::{RatatuiPlugins, event::KeyEvent};
+use bevy_ratatui::{RatatuiPlugins, event::ResizeEvent};
 use clap::Parser;
 // use component::Position;
 use serde::{Deserialize, Serialize};
@@ -17,14 +17,19 @@ use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitEx
 mod cli;
 mod component;
 mod config;
+mod effect;
 mod event;
 mod game;
 mod game_event;
+mod gamelog;
 mod gamelogic;
+mod item;
 mod map;
 mod player;
 mod rng;
+mod status;
 mod system;
+mod targeting;
 mod ui;
 mod ui_component;
 mod utils;
@@ -34,12 +39,15 @@ use cli::CliArgs;
 pub use component::*;
 pub use config::*;
 use event::*;
-use game::CurrentGameData;
+use game::{CurrentGameData, GameTime};
 pub use game_event::*;
+use gamelog::GameLog;
 use gamelogic::Rollable;
 pub use player::*;
 pub use rng::*;
+pub use status::*;
 use system::*;
+use targeting::Targeting;
 pub use ui::*;
 pub use ui_component::*;
 use worldgen::generate_world;
@@ -58,6 +66,17 @@ pub const VERSION_STRING: &str = concat!(
 
 pub const MAIN_MENU_NAME: &str = "main_menu";
 pub const GAME_UI_NAME: &str = "game_ui";
+pub const LEVEL_UP_NAME: &str = "level_up";
+pub const KEY_HINTS_NAME: &str = "key_hints";
+pub const KEY_BINDINGS_NAME: &str = "key_bindings";
+pub const SPELL_MENU_NAME: &str = "spell_menu";
+pub const ITEM_MENU_NAME: &str = "item_menu";
+pub const INVENTORY_NAME: &str = "inventory";
+pub const EQUIPMENT_NAME: &str = "equipment";
+pub const ITEM_DETAIL_NAME: &str = "item_detail";
+
+/// Number of game log lines passed on to the game UI.
+const GAME_LOG_LINES: usize = 20;
 
 // Later maybe we want a Menu state with various SubStates for the different menus?
 #[derive(States, Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -68,6 +87,15 @@ pub enum GameState {
     WorldGen,
     NewGame,
     InGame,
+    LevelUp,
+    Targeting,
+    SpellMenu,
+    ItemMenu,
+    Inventory,
+    Equipment,
+    ItemDetail,
+    ChooseDirection,
+    KeyBindings,
 }
 
 #[derive(SubStates, Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -95,17 +123,24 @@ pub enum TurnState {
 // }
 
 fn main() {
+    ///// Parse CLI args
+    let args = CliArgs::parse();
+    let paths = config::Paths::resolve(args.config_dir.clone(), args.data_dir.clone());
+
     ////// Start logger
     let timestamp = chrono::Local::now().format("%Y-%m-%d_%H:%M:%S").to_string();
     let log_file = format!("{}_{}.log", env!("CARGO_PKG_NAME"), timestamp);
+    let log_dir = paths.state_dir.join("logs");
+    // Relative to the symlink, so it holds wherever the state directory is
     let log_file_path = std::path::Path::new("logs").join(log_file.clone());
     let file_appender =
-        tracing_appender::rolling::RollingFileAppender::new(tracing_appender::rolling::Rotation::NEVER, "logs", log_file);
+        tracing_appender::rolling::RollingFileAppender::new(tracing_appender::rolling::Rotation::NEVER, &log_dir, log_file);
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
 
     // Create symlink to current log file
-    let symlink_path = std::path::Path::new("current-log");
-    if symlink_path.exists() {
+    let symlink_path = paths.state_dir.join("current-log");
+    let symlink_path = symlink_path.as_path();
+    if symlink_path.symlink_metadata().is_ok() {
         std::fs::remove_file(symlink_path).unwrap_or_else(|e| warn!("Failed to remove old symlink: {}", e));
     }
     std::os::unix::fs::symlink(log_file_path, symlink_path).unwrap_or_else(|e| warn!("Failed to create symlink: {}", e));
@@ -118,11 +153,22 @@ fn main() {
     info!("{} {} starting", env!("CARGO_PKG_NAME"), VERSION_STRING);
 
     ///// Load config
-    debug!("Loading config files");
-    config::load_config(None, None);
-
-    ///// Parse CLI args
-    let args = CliArgs::parse();
+    info!("Using paths: {:?}", paths);
+    let mut report = config::load_config(&paths).err().unwrap_or_default();
+    config::init_paths(paths);
+    info!("Loading UI config...");
+    let uiconfig = UIConfig::new().unwrap_or_else(|problems| {
+        report.extend(problems);
+        UIConfig::default()
+    });
+    if !report.is_empty() {
+        for problem in &report.problems {
+            error!("{problem}");
+        }
+        eprint!("{report}");
+        eprintln!("Fix these and start the game again.");
+        exit(1);
+    }
 
     let seed;
     if args.seed == 0 {
@@ -143,7 +189,12 @@ fn main() {
     ///// Build Bevy App and run
     let frame_time = Duration::from_secs_f32(1.0 / 60.0);
 
-    App::new()
+    let mut app = App::new();
+    if args.watch {
+        info!("Watching config and data files for changes");
+        app.insert_resource(ConfigWatcher::new());
+    }
+    app
         // .add_plugins(DefaultPlugins)
         .add_plugins((
             MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(frame_time)),
@@ -158,14 +209,21 @@ fn main() {
         .add_sub_state::<MenuState>()
         .add_sub_state::<TurnState>()
         // Resources
-        .init_resource::<UIConfig>()
+        .insert_resource(uiconfig)
         .init_resource::<UIComponents>()
         .init_resource::<CurrentGameData>()
+        .init_resource::<GameTime>()
+        .init_resource::<GameLog>()
+        .init_resource::<KeySequence>()
         .insert_resource(seed)
         // Events
         .add_event::<GameEvent>()
         .add_event::<PlayerMoveRelativeEvent>()
         .add_event::<PlayerSpentEnergy>()
+        .add_event::<GameTick>()
+        .add_event::<MapChanged>()
+        .add_event::<effect::EffectEvent>()
+        .add_event::<item::LootDropEvent>()
         // Startup schedule
         .add_systems(PreStartup, setup_ui_components)
         .add_systems(Startup, enter_main_menu)
@@ -191,11 +249,27 @@ fn main() {
         // .add_systems(PreUpdate, cleanup_component_system::<Intent>.before(keyboard_input_system))
         .add_systems(
             Update,
-            keyboard_input_system, /*.run_if(in_state(TurnState::PlayersTurn).or(in_state(GameState::Menu)))*/
+            (
+                keyboard_input_system, /*.run_if(in_state(TurnState::PlayersTurn).or(in_state(GameState::Menu)))*/
+                key_hints_system.run_if(resource_changed::<KeySequence>),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            repeat_event_system
+                .run_if(in_state(TurnState::PlayersTurn))
+                .after(keyboard_input_system)
+                .before(player_game_event_handler),
         )
+        .add_systems(OnExit(GameState::InGame), stop_repeating_system)
         // .add_systems(PreUpdate, log_positions)
-        .add_systems(Update, ui_render_system)
-        .add_systems(Update, game_event_handler)
+        .add_systems(
+            Update,
+            ui_render_system.run_if(resource_changed::<UIComponents>.or(on_event::<ResizeEvent>)),
+        )
+        .add_systems(Update, (resize_system, game_event_handler).chain())
+        .add_systems(Update, config_reload_system.run_if(resource_exists::<ConfigWatcher>))
         .add_systems(Update, player_game_event_handler.run_if(in_state(TurnState::PlayersTurn)))
         //
         // Player's Turn
@@ -204,6 +278,20 @@ fn main() {
         //     OnEnter(TurnState::PlayersTurn),
         //     (cleanup_component_system::<Intent>, cleanup_component_system::<PerformAction>),
         // )
+        .add_systems(
+            Update,
+            activity_system
+                .run_if(in_state(TurnState::PlayersTurn))
+                .after(player_game_event_handler)
+                .before(process_intents_system),
+        )
+        .add_systems(
+            Update,
+            status_intent_system
+                .run_if(in_state(GameState::InGame))
+                .after(activity_system)
+                .before(process_intents_system),
+        )
         .add_systems(
             Update,
             process_intents_system
@@ -212,7 +300,27 @@ fn main() {
         )
         .add_systems(
             Update,
-            (player_move_system, player_spent_energy_system, update_player_pos).run_if(in_state(TurnState::PlayersTurn)),
+            (
+                player_move_system,
+                player_spent_energy_system,
+                update_player_pos,
+                melee_combat_system,
+                (
+                    throw_system,
+                    fire_system,
+                    use_item_system,
+                    equip_system,
+                    drop_system,
+                    pickup_system,
+                    door_system,
+                    bash_system,
+                    cast_spell_system,
+                    effect_system,
+                )
+                    .chain(),
+            )
+                .after(process_intents_system)
+                .run_if(in_state(TurnState::PlayersTurn)),
         )
         //
         // Not Player's Turn
@@ -228,20 +336,94 @@ fn main() {
         )
         .add_systems(
             PostUpdate,
-            (process_intents_system, movement_action_system, spend_energy_system)
+            (
+                process_intents_system,
+                movement_action_system,
+                melee_combat_system,
+                spend_energy_system,
+                end_npc_turn_system,
+            )
+                .chain()
                 .run_if(in_state(TurnState::NotPlayersTurn)),
         )
         //
         // Other stuff
         //
-        .add_systems(PostUpdate, update_map.run_if(in_state(GameState::InGame))) // TODO: only run on some Map Update event?
+        .add_systems(PostUpdate, regeneration_system.run_if(in_state(GameState::InGame)))
+        .add_systems(PostUpdate, level_up_system.run_if(in_state(GameState::InGame)))
+        .add_systems(PostUpdate, loot_drop_system.run_if(in_state(GameState::InGame)))
+        .add_systems(
+            PostUpdate,
+            (derived_stats_system, curse_penalty_system).run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(Update, start_targeting_system.run_if(in_state(TurnState::PlayersTurn)))
+        .add_systems(Update, targeting_system.run_if(in_state(GameState::Targeting)))
+        .add_systems(Update, select_spell_system.run_if(in_state(GameState::SpellMenu)))
+        .add_systems(Update, open_item_menu_system.run_if(in_state(TurnState::PlayersTurn)))
+        .add_systems(
+            Update,
+            item_action_system.run_if(in_state(GameState::ItemMenu).or(in_state(GameState::ItemDetail))),
+        )
+        .add_systems(
+            Update,
+            open_item_detail_system.run_if(in_state(GameState::Inventory).or(in_state(GameState::Equipment))),
+        )
+        .add_systems(Update, close_item_detail_system.run_if(in_state(GameState::ItemDetail)))
+        .add_systems(
+            Update,
+            (door_prompt_system, bash_prompt_system, fire_prompt_system).run_if(in_state(TurnState::PlayersTurn)),
+        )
+        .add_systems(Update, choose_direction_system.run_if(in_state(GameState::ChooseDirection)))
+        .add_systems(OnExit(GameState::ChooseDirection), close_direction_prompt)
+        .add_systems(
+            Update,
+            (skill_allocation_system, level_up_screen_system)
+                .chain()
+                .run_if(in_state(GameState::LevelUp)),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                status_tick_system::<Poisoned>,
+                status_tick_system::<Confused>,
+                status_tick_system::<Slowed>,
+                status_tick_system::<Hasted>,
+                status_tick_system::<Blinded>,
+                status_tick_system::<Regenerating>,
+                status_tick_system::<Paralysed>,
+            )
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(
+            PostUpdate,
+            (map_changed_system, visibility_system, combat_status_system, update_game_ui)
+                .chain()
+                .after(end_npc_turn_system)
+                .run_if(in_state(GameState::InGame).or(in_state(GameState::Targeting))),
+        )
         //
         // State transition schedules
         //
         .add_systems(OnEnter(MenuState::MainMenu), show_main_menu)
         .add_systems(OnExit(MenuState::MainMenu), hide_main_menu)
         .add_systems(OnEnter(GameState::InGame), show_game_ui)
-        .add_systems(OnExit(GameState::InGame), hide_game_ui)
+        .add_systems(OnEnter(GameState::Menu), hide_game_ui)
+        .add_systems(OnEnter(GameState::LevelUp), show_level_up)
+        .add_systems(OnExit(GameState::LevelUp), hide_level_up)
+        .add_systems(OnExit(GameState::Targeting), cleanup_targeting)
+        .add_systems(OnEnter(GameState::SpellMenu), show_spell_menu)
+        .add_systems(OnExit(GameState::SpellMenu), hide_spell_menu)
+        .add_systems(OnEnter(GameState::ItemMenu), show_item_menu)
+        .add_systems(OnExit(GameState::ItemMenu), hide_item_menu)
+        .add_systems(OnEnter(GameState::Inventory), show_inventory)
+        .add_systems(OnExit(GameState::Inventory), hide_inventory)
+        .add_systems(OnEnter(GameState::Equipment), show_equipment)
+        .add_systems(OnExit(GameState::Equipment), hide_equipment)
+        .add_systems(OnEnter(GameState::ItemDetail), show_item_detail)
+        .add_systems(OnExit(GameState::ItemDetail), hide_item_detail)
+        .add_systems(Update, keybinding_editor_system.run_if(in_state(GameState::KeyBindings)))
+        .add_systems(OnEnter(GameState::KeyBindings), show_keybinding_editor)
+        .add_systems(OnExit(GameState::KeyBindings), hide_keybinding_editor)
         .add_systems(OnEnter(GameState::WorldGen), generate_world)
         .add_systems(OnEnter(GameState::NewGame), setup_new_game)
         .run();
@@ -300,11 +482,30 @@ fn game_event_handler(
             GameEvent::ShowMainMenu => {
                 next_state.set(GameState::Menu);
             }
+            GameEvent::ShowLevelUp => {
+                next_state.set(GameState::LevelUp);
+            }
+            GameEvent::ReturnToGame => {
+                next_state.set(GameState::InGame);
+            }
+            GameEvent::ShowSpellMenu => {
+                next_state.set(GameState::SpellMenu);
+            }
+            GameEvent::ShowInventory => {
+                next_state.set(GameState::Inventory);
+            }
+            GameEvent::ShowEquipment => {
+                next_state.set(GameState::Equipment);
+            }
+            GameEvent::ShowKeyBindings => {
+                next_state.set(GameState::KeyBindings);
+            }
             _ => {}
         }
 
-        // Now check if any UI components should handle this Event
-        for (name, uicomponent) in ui_components.comps.iter_mut() {
+        // Now check if any visible UI components should handle this Event, all of them relayout on resizes
+        let resize = matches!(event, GameEvent::Resize(..));
+        for (name, uicomponent) in ui_components.comps.iter_mut().filter(|(_, c)| c.visible || resize) {
             if let Ok(Some(ev)) = uicomponent.component.update(event.clone()) {
                 debug!("UI component '{}' produced new event '{:?}'", name, ev);
                 events_to_send.push(ev.clone());
@@ -325,40 +526,87 @@ fn player_game_event_handler(
 ) {
     for event in event_queue.read() {
         debug!("Received GameEvent: {:?}", event);
-        #[allow(clippy::single_match)]
+        let player = cgd.player.unwrap();
         match event {
             GameEvent::PlayerMoveRelative { dx, dy } => {
                 commands
-                    .entity(cgd.player.unwrap())
+                    .entity(player)
+                    .remove::<Activity>()
                     .insert(Intent::MoveRelative { dx: *dx, dy: *dy });
             }
-            _ => {}
-        }
-    }
-}
-
-fn keyboard_input_system(
-    mut events: EventReader<KeyEvent>,
-    mut game_events: EventWriter<GameEvent>,
-    uiconfig: Res<UIConfig>,
-    state: Res<State<GameState>>,
-) {
-    for event in events.read() {
-        debug!("KeyEvent received: {event:?}");
-        if let Some(keymap) = uiconfig.keybindings.get(&state) {
-            // debug!("keymap found: {keymap:?}");
-            if let Some(ge) = keymap.get(&vec![crossterm::event::KeyEvent::new(event.code, event.modifiers)]) {
-                debug!("Key pressed for game event: {ge:?}");
-                game_events.write(ge.clone());
-                //     // action_tx.send(action.clone())?;
-                // } else {
-                //     // If the key was not handled as a single key action, then consider it for multi-key combinations
-                //     // self.last_tick_key_events.push(key);
-                //     // if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                //     //     log::info!("Got action: {action:?}");
-                //     //     action_tx.send(action.clone())?;
-                //     // }
+            GameEvent::PlayerWait => {
+                commands.entity(player).remove::<Activity>().insert(Intent::Nothing);
+            }
+            GameEvent::PlayerRest => {
+                commands.entity(player).insert(Activity::Resting);
+            }
+            GameEvent::PlayerAutoExplore => {
+                commands.entity(player).insert(Activity::AutoExploring);
             }
+            GameEvent::PlayerThrow { item, x, y } => {
+                commands.entity(player).remove::<Activity>().insert(Intent::Throw {
+                    item: *item,
+                    x: *x,
+                    y: *y,
+                });
+            }
+            GameEvent::PlayerFire { weapon, ammo, x, y } => {
+                commands.entity(player).remove::<Activity>().insert(Intent::Fire {
+                    weapon: *weapon,
+                    ammo: *ammo,
+                    x: *x,
+                    y: *y,
+                });
+            }
+            GameEvent::PlayerUseItem { item, x, y } => {
+                commands.entity(player).remove::<Activity>().insert(Intent::UseItem {
+                    item: *item,
+                    x: *x,
+                    y: *y,
+                });
+            }
+            GameEvent::PlayerEquip(item) => {
+                commands
+                    .entity(player)
+                    .remove::<Activity>()
+                    .insert(Intent::Equip { item: *item });
+            }
+            GameEvent::PlayerUnequip(item) => {
+                commands
+                    .entity(player)
+                    .remove::<Activity>()
+                    .insert(Intent::Unequip { item: *item });
+            }
+            GameEvent::PlayerDrop(item) => {
+                commands
+                    .entity(player)
+                    .remove::<Activity>()
+                    .insert(Intent::Drop { item: *item });
+            }
+            GameEvent::PlayerPickUp => {
+                commands.entity(player).remove::<Activity>().insert(Intent::PickUp);
+            }
+            GameEvent::PlayerOperateDoor { action, x, y } => {
+                commands.entity(player).remove::<Activity>().insert(Intent::OperateDoor {
+                    action: *action,
+                    x: *x,
+                    y: *y,
+                });
+            }
+            GameEvent::PlayerBash { x, y } => {
+                commands
+                    .entity(player)
+                    .remove::<Activity>()
+                    .insert(Intent::Bash { x: *x, y: *y });
+            }
+            GameEvent::PlayerCast { spell, x, y } => {
+                commands.entity(player).remove::<Activity>().insert(Intent::Cast {
+                    spell: *spell,
+                    x: *x,
+                    y: *y,
+                });
+            }
+            _ => {}
         }
     }
 }
@@ -401,39 +649,49 @@ fn hide_game_ui(mut uicomps: ResMut<UIComponents>) {
     c.visible = false;
 }
 
+fn show_level_up(mut uicomps: ResMut<UIComponents>) {
+    let c = uicomps
+        .comps
+        .get_mut(LEVEL_UP_NAME)
+        .unwrap_or_else(|| panic!("Couldn't find level_up UI component."));
+    c.visible = true;
+}
+
+fn hide_level_up(mut uicomps: ResMut<UIComponents>) {
+    let c = uicomps
+        .comps
+        .get_mut(LEVEL_UP_NAME)
+        .unwrap_or_else(|| panic!("Couldn't find level_up UI component."));
+    c.visible = false;
+}
+
 fn setup_new_game(
     cgd: Res<CurrentGameData>,
+    uiconfig: Res<UIConfig>,
     mut uicomps: ResMut<UIComponents>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     // Update GameUi with current map
     let mut game_ui = ui::components::GameUi::new();
-    game_ui.set_map(cgd.maps.map[cgd.current_map].clone());
+    game_ui.render_map(&cgd.maps.map[cgd.current_map]);
+    if let Err(e) = game_ui.register_config_handler(uiconfig.clone()) {
+        error!("Failed to configure the game UI: {:?}", e);
+    }
     uicomps.comps.insert(
         GAME_UI_NAME.to_string(),
         UIComponentData {
             component: Box::new(game_ui) as Box<dyn UIComponent>,
             visible: true,
+            layer: 0,
         },
     );
 
     next_state.set(GameState::InGame);
 }
 
-fn setup_ui_components(mut uiconfig: ResMut<UIConfig>, mut uicomps: ResMut<UIComponents>) {
+fn setup_ui_components(mut uicomps: ResMut<UIComponents>) {
     info!("Setting up UI components...");
 
-    info!("Loading UI config...");
-    let load_uiconfig = UIConfig::new();
-    match load_uiconfig {
-        Ok(load_uiconfig) => {
-            uiconfig.config = load_uiconfig.config;
-            uiconfig.keybindings = load_uiconfig.keybindings;
-            uiconfig.styles = load_uiconfig.styles;
-        }
-        Err(e) => panic!("Error while loading ui config: {e}"),
-    }
-
     info!("Creating initial UI components...");
 
     // Main Menu UI component
@@ -443,16 +701,21 @@ fn setup_ui_components(mut uiconfig: ResMut<UIConfig>, mut uicomps: ResMut<UICom
         .add_item(("Generate World", Some(GameEvent::GenerateWorld)))
         .add_item(("New Game", Some(GameEvent::StartNewGame)))
         .add_item(("Load Game", None))
+        .add_item(("Key Bindings", Some(GameEvent::ShowKeyBindings)))
         .add_item(("Quit", Some(GameEvent::Quit)));
 
     // UIMap UI component
     let game_ui = ui::components::GameUi::new();
 
+    // Level up screen, shown on top of the game UI
+    let level_up = ui::components::LevelUpScreen::new();
+
     uicomps.comps.insert(
         MAIN_MENU_NAME.to_string(),
         UIComponentData {
             component: Box::new(main_menu) as Box<dyn UIComponent>,
             visible: false,
+            layer: 0,
         },
     );
     uicomps.comps.insert(
@@ -460,40 +723,134 @@ fn setup_ui_components(mut uiconfig: ResMut<UIConfig>, mut uicomps: ResMut<UICom
         UIComponentData {
             component: Box::new(game_ui) as Box<dyn UIComponent>,
             visible: false,
+            layer: 0,
+        },
+    );
+    uicomps.comps.insert(
+        LEVEL_UP_NAME.to_string(),
+        UIComponentData {
+            component: Box::new(level_up) as Box<dyn UIComponent>,
+            visible: false,
+            layer: 1,
+        },
+    );
+    uicomps.comps.insert(
+        KEY_HINTS_NAME.to_string(),
+        UIComponentData {
+            component: Box::new(ui::components::KeyHints::new()) as Box<dyn UIComponent>,
+            visible: false,
+            layer: 10,
         },
     );
 }
 
-fn update_map(cgd: Res<CurrentGameData>, mut uicomps: ResMut<UIComponents>, query: Query<(&Position, &Render)>) {
-    let mut result: Vec<(Position, Render)> = Vec::new();
-
-    // Find renderable entities on current map
-    for (position, render) in query {
-        debug_once!(
-            "Found renderable entity at {},{} in map {}",
-            position.x,
-            position.y,
-            position.map
-        );
-        if position.map == cgd.current_map {
-            result.push((*position, *render));
+/// Keeps the game UI up to date, updating it in place with only what changed since the last frame:
+/// the map, the entities shown on it, the log, the turn, the player's stats and the targeting
+/// overlay. The map isn't copied, only the part in view is rendered from it when it or anything on
+/// it changed. The UI is only marked for redrawing when something did change.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_game_ui(
+    cgd: Res<CurrentGameData>,
+    time: Res<GameTime>,
+    log: Res<GameLog>,
+    mut ticks: EventReader<GameTick>,
+    mut uicomps: ResMut<UIComponents>,
+    query: Query<(&Position, &Render)>,
+    moved: Query<(), (With<Render>, Or<(Changed<Position>, Changed<Render>)>)>,
+    mut removed_renders: RemovedComponents<Render>,
+    mut removed_positions: RemovedComponents<Position>,
+    player_query: Query<(&DetailedName, Ref<Stats>, EntityRef), With<Player>>,
+    targeting: Option<Res<Targeting>>,
+) {
+    let Some(game_ui) = uicomps
+        .bypass_change_detection()
+        .comps
+        .get_mut(GAME_UI_NAME)
+        .and_then(|c| c.component.downcast_mut::<ui::components::GameUi>())
+    else {
+        return;
+    };
+    let map = &cgd.maps.map[cgd.current_map];
+    let turn_ended = ticks.read().count() > 0;
+    let map_changed = game_ui.map_revision() != map.revision();
+    let entities_changed = !moved.is_empty() || removed_renders.read().count() > 0 || removed_positions.read().count() > 0;
+    let mut changed = false;
+    let mut view_changed = map_changed || game_ui.map_view_outdated();
+
+    // What can be seen depends on the map, so the entities are checked again when it changes
+    if map_changed || entities_changed {
+        let mut result: Vec<(Position, Render)> = Vec::new();
+        for (position, render) in query {
+            debug_once!(
+                "Found renderable entity at {},{} in map {}",
+                position.x,
+                position.y,
+                position.map
+            );
+            if position.map == cgd.current_map && (render.always || map.is_visible(position.x as usize, position.y as usize))
+            {
+                result.push((*position, *render));
+            }
         }
+
+        // Sort by rendering order
+        result.sort_by_key(|r| std::cmp::Reverse(r.1.order));
+        game_ui.set_entities(result);
+        // The camera follows the player on maps bigger than the screen
+        let player_pos = player_query.single().ok().and_then(|(_, _, e)| e.get::<Position>().copied());
+        game_ui.set_player_pos(player_pos.unwrap_or(cgd.player_pos));
+        view_changed = true;
     }
 
-    // Sort by rendering order
-    result.sort_by(|a, b| b.1.order.cmp(&a.1.order));
+    if log.is_changed() {
+        game_ui.set_log(log.latest(GAME_LOG_LINES));
+        changed = true;
+    }
+    if time.is_changed() {
+        game_ui.set_turn(time.tick);
+        changed = true;
+    }
 
-    // Update Game UI
-    let mut game_ui = ui::components::GameUi::new();
-    game_ui.set_map(cgd.maps.map[cgd.current_map].clone());
-    game_ui.set_entities(result);
-    uicomps.comps.insert(
-        GAME_UI_NAME.to_string(),
-        UIComponentData {
-            component: Box::new(game_ui) as Box<dyn UIComponent>,
-            visible: true,
-        },
-    );
+    if let Ok((name, stats, entity)) = player_query.single() {
+        if stats.is_changed() || turn_ended {
+            game_ui.set_player_stats(name.full.clone(), stats.clone());
+            game_ui.set_next_level_xp(CFG.lock().unwrap().config.experience.xp_for_level(stats.lv + 1));
+            changed = true;
+        }
+        // Statuses are added and removed through commands, so they're compared instead
+        let statuses = status_names(&entity);
+        if game_ui.statuses() != statuses.as_slice() {
+            game_ui.set_statuses(statuses);
+            changed = true;
+        }
+    }
+
+    match targeting {
+        Some(targeting) if targeting.is_changed() || !game_ui.has_targeting() => {
+            game_ui.set_targeting(Some(ui::components::TargetingOverlay {
+                action: targeting.purpose.description(),
+                cursor: (targeting.cursor.x, targeting.cursor.y),
+                path: targeting.path.clone(),
+                blocked: !targeting.reaches_cursor(),
+                description: targeting.description.clone(),
+            }));
+            view_changed = true;
+        }
+        None if game_ui.has_targeting() => {
+            game_ui.set_targeting(None);
+            view_changed = true;
+        }
+        _ => {}
+    }
+
+    if view_changed {
+        game_ui.render_map(map);
+        changed = true;
+    }
+
+    if changed {
+        uicomps.set_changed();
+    }
 }
 
 /// System which removes all components of type T from all entities that have them.
@@ -1,4 +1,5 @@
-use super::{BORDER_TILE, BuilderMap, MetaMapBuilder};
+use super::{BuilderMap, MetaMapBuilder};
+use crate::map::TileType;
 
 pub struct Borders;
 
@@ -14,26 +15,12 @@ impl Borders {
     }
 
     pub fn build_map(&mut self, build_data: &mut BuilderMap) {
-        build_data
-            .map
-            .tiles
-            .insert_col(0, vec![BORDER_TILE.clone(); build_data.height]);
-        build_data.width += 1;
-        build_data.map.width += 1;
-
-        build_data.map.tiles.push_col(vec![BORDER_TILE.clone(); build_data.height]);
-        build_data.width += 1;
-        build_data.map.width += 1;
-
-        build_data
-            .map
-            .tiles
-            .insert_row(0, vec![BORDER_TILE.clone(); build_data.width]);
-        build_data.height += 1;
-        build_data.map.height += 1;
-
-        build_data.map.tiles.push_row(vec![BORDER_TILE; build_data.width]);
-        build_data.height += 1;
-        build_data.map.height += 1;
+        let map = &mut build_data.map;
+        map.insert_col(0, TileType::Border);
+        map.insert_col(map.width, TileType::Border);
+        map.insert_row(0, TileType::Border);
+        map.insert_row(map.height, TileType::Border);
+        build_data.width = map.width;
+        build_data.height = map.height;
     }
 }
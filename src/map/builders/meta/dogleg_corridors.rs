@@ -0,0 +1,65 @@
+use bevy::log::debug;
+
+use super::{BuilderMap, MetaMapBuilder};
+use crate::{map::TileType, rng};
+
+/// Meta map builder which connects each room to the next one with an L-shaped corridor.
+/// Only tiles which had to be dug out are recorded as part of a corridor.
+pub struct DoglegCorridors;
+
+impl MetaMapBuilder for DoglegCorridors {
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        self.build_map(build_data);
+    }
+}
+
+impl DoglegCorridors {
+    pub fn new() -> Box<DoglegCorridors> {
+        Box::new(DoglegCorridors {})
+    }
+
+    fn dig(build_data: &mut BuilderMap, corridor: &mut Vec<(i32, i32)>, x: i32, y: i32) {
+        if build_data.map.get_tile_type(x as usize, y as usize) == TileType::Wall {
+            build_data.map.define_tile(x, y, TileType::Floor);
+            corridor.push((x, y));
+        }
+    }
+
+    pub fn build_map(&mut self, build_data: &mut BuilderMap) {
+        debug!("In DoglegCorridors meta map builder build_map()");
+        let Some(rooms) = build_data.rooms.clone() else {
+            panic!("DoglegCorridors requires rooms!");
+        };
+
+        for pair in rooms.windows(2) {
+            let (x1, y1) = pair[0].center();
+            let (x2, y2) = pair[1].center();
+            let mut corridor = Vec::new();
+            if rng::range(0, 2) == 0 {
+                for x in steps(x1, x2) {
+                    Self::dig(build_data, &mut corridor, x, y1);
+                }
+                for y in steps(y1, y2) {
+                    Self::dig(build_data, &mut corridor, x2, y);
+                }
+            } else {
+                for y in steps(y1, y2) {
+                    Self::dig(build_data, &mut corridor, x1, y);
+                }
+                for x in steps(x1, x2) {
+                    Self::dig(build_data, &mut corridor, x, y2);
+                }
+            }
+            build_data.corridors.push(corridor);
+        }
+    }
+}
+
+/// The coordinates from `from` to `to`, both included, in that order.
+fn steps(from: i32, to: i32) -> Vec<i32> {
+    if from <= to {
+        (from..=to).collect()
+    } else {
+        (to..=from).rev().collect()
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::log::debug;
+
+use super::{BuilderMap, MetaMapBuilder};
+use crate::{map::TileType, rng};
+
+/// Meta map builder which puts doors where corridors meet rooms: on the first and last tile of
+/// each corridor, if it's a narrow passage between walls. Most doors are closed, some are open
+/// and a few are locked.
+pub struct DoorPlacement;
+
+impl MetaMapBuilder for DoorPlacement {
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        self.build_map(build_data);
+    }
+}
+
+impl DoorPlacement {
+    pub fn new() -> Box<DoorPlacement> {
+        Box::new(DoorPlacement {})
+    }
+
+    /// A door fits between two walls, with floor on the other two sides.
+    fn door_possible(build_data: &BuilderMap, x: i32, y: i32) -> bool {
+        let map = &build_data.map;
+        if x < 1 || y < 1 || x + 1 >= map.width as i32 || y + 1 >= map.height as i32 {
+            return false;
+        }
+        let tile = |dx: i32, dy: i32| map.get_tile_type((x + dx) as usize, (y + dy) as usize);
+        if tile(0, 0) != TileType::Floor {
+            return false;
+        }
+        let (wall, floor) = (TileType::Wall, TileType::Floor);
+        (tile(-1, 0) == wall && tile(1, 0) == wall && tile(0, -1) == floor && tile(0, 1) == floor)
+            || (tile(0, -1) == wall && tile(0, 1) == wall && tile(-1, 0) == floor && tile(1, 0) == floor)
+    }
+
+    pub fn build_map(&mut self, build_data: &mut BuilderMap) {
+        debug!("In DoorPlacement meta map builder build_map()");
+        let corridors = build_data.corridors.clone();
+        for corridor in corridors.iter() {
+            let ends = [corridor.first(), corridor.last()];
+            for &(x, y) in ends.into_iter().flatten() {
+                if !Self::door_possible(build_data, x, y) {
+                    continue;
+                }
+                let door = match rng::roll_dice(1, 10) {
+                    1 => TileType::DoorLocked,
+                    2 | 3 => TileType::DoorOpen,
+                    _ => TileType::DoorClosed,
+                };
+                debug!("Placing door at {},{}", x, y);
+                build_data.map.define_tile(x, y, door);
+            }
+        }
+    }
+}
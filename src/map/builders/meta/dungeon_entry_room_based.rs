@@ -1,7 +1,7 @@
 use bevy::log::debug;
 
 use super::{BuilderMap, MetaMapBuilder};
-use crate::{component::Position, map::DUNGEON_ENTRY_TILE};
+use crate::{component::Position, map::TileType};
 
 pub struct DungeonEntryRoomBased;
 
@@ -25,7 +25,7 @@ impl DungeonEntryRoomBased {
                 map: build_data.map.id,
             });
             // let upstairs_idx = build_data.map.idx(start_pos.0, start_pos.1);
-            build_data.map.define_tile(start_pos.0, start_pos.1, DUNGEON_ENTRY_TILE);
+            build_data.map.define_tile(start_pos.0, start_pos.1, TileType::DungeonEntry);
             // build_data.map.starting_position = build_data.starting_position.unwrap();
             debug!("Added dungeon entry at {},{}", start_pos.0, start_pos.1);
         } else {
@@ -0,0 +1,78 @@
+use bevy::log::debug;
+
+use super::{BuilderMap, MetaMapBuilder};
+use crate::{
+    CFG,
+    component::Position,
+    map::{MapRect, TileType},
+    rng,
+};
+
+/// How often to look for a free floor tile in a room before giving up on an item.
+const PLACEMENT_TRIES: usize = 10;
+
+/// Meta map builder which scatters items from the level's loot table across the rooms, and puts
+/// a key on levels with locked doors.
+/// The items are only added to the spawn list, they're spawned once the world exists.
+pub struct LootSpawner;
+
+impl MetaMapBuilder for LootSpawner {
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        self.build_map(build_data);
+    }
+}
+
+impl LootSpawner {
+    pub fn new() -> Box<LootSpawner> {
+        Box::new(LootSpawner {})
+    }
+
+    /// Puts the item on a free floor tile in the room, if one is found.
+    fn place(build_data: &mut BuilderMap, room: MapRect, name: String, count: i32) {
+        for _ in 0..PLACEMENT_TRIES {
+            let (x, y) = (rng::range(room.x1, room.x2 + 1), rng::range(room.y1, room.y2 + 1));
+            let free = build_data.spawn_list.iter().all(|(p, ..)| p.x != x || p.y != y);
+            if build_data.map.in_bounds(x, y)
+                && build_data.map.get_tile_type(x as usize, y as usize) == TileType::Floor
+                && free
+            {
+                debug!("Placing {} x {} at {},{}", count, name, x, y);
+                let pos = Position {
+                    x,
+                    y,
+                    map: build_data.map.id,
+                };
+                build_data.spawn_list.push((pos, name, count));
+                return;
+            }
+        }
+    }
+
+    pub fn build_map(&mut self, build_data: &mut BuilderMap) {
+        let Some(rooms) = build_data.rooms.clone().filter(|r| !r.is_empty()) else {
+            debug!("No rooms to put loot in");
+            return;
+        };
+        let depth = build_data.map.id;
+        let cfg = CFG.lock().unwrap();
+
+        // Levels with locked doors get a key, in the room the corridors start from.
+        let locked = build_data.map.tile_types().iter().any(|t| *t == TileType::DoorLocked);
+        if let Some(key) = cfg.config.loot.key.as_ref().filter(|_| locked) {
+            Self::place(build_data, rooms[0], key.clone(), 1);
+        }
+
+        let Some(level) = cfg.config.loot.level(depth) else {
+            debug!("No loot for depth {}", depth);
+            return;
+        };
+        let amount = rng::roll_str(&level.items);
+        for _ in 0..amount {
+            let Some((name, count)) = cfg.config.loot.roll(&level.table, depth) else {
+                continue;
+            };
+            let room = rooms[rng::range(0, rooms.len() as i32) as usize];
+            Self::place(build_data, room, name, count);
+        }
+    }
+}
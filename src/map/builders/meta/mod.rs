@@ -1,7 +1,10 @@
 pub mod borders;
+pub mod dogleg_corridors;
+pub mod door_placement;
 pub mod dungeon_entry_room_based;
+pub mod loot_spawner;
 pub mod reveal_all;
 pub mod room_drawer;
 pub mod room_sorter;
 
-pub use crate::map::{BORDER_TILE, BuilderMap, MetaMapBuilder};
+pub use crate::map::{BuilderMap, MetaMapBuilder};
@@ -2,7 +2,7 @@ use bevy::log::debug;
 
 use super::{BuilderMap, MetaMapBuilder};
 use crate::{
-    map::{FLOOR_TILE, MapRect},
+    map::{MapRect, TileType},
     rng,
     utils::distance2d_pythagoras,
 };
@@ -25,7 +25,7 @@ impl RoomDrawer {
         debug!("building room in rectangle shape: {:?}", room);
         for y in room.y1..=room.y2 {
             for x in room.x1..=room.x2 {
-                build_data.map.define_tile(x, y, FLOOR_TILE);
+                build_data.map.define_tile(x, y, TileType::Floor);
             }
         }
     }
@@ -40,7 +40,7 @@ impl RoomDrawer {
                 // debug!("{x}, {y}: distance {distance}");
                 if distance <= radius {
                     // debug!("within circle");
-                    build_data.map.define_tile(x, y, FLOOR_TILE);
+                    build_data.map.define_tile(x, y, TileType::Floor);
                 }
             }
         }
@@ -24,10 +24,10 @@ impl RoomSorter {
 
     pub fn sorter(&mut self, build_data: &mut BuilderMap) {
         match self.sort_by {
-            RoomSort::Leftmost => build_data.rooms.as_mut().unwrap().sort_by(|a, b| a.x1.cmp(&b.x1)),
-            RoomSort::Rightmost => build_data.rooms.as_mut().unwrap().sort_by(|a, b| b.x2.cmp(&a.x2)),
-            RoomSort::Topmost => build_data.rooms.as_mut().unwrap().sort_by(|a, b| a.y1.cmp(&b.y1)),
-            RoomSort::Bottommost => build_data.rooms.as_mut().unwrap().sort_by(|a, b| b.y2.cmp(&a.y2)),
+            RoomSort::Leftmost => build_data.rooms.as_mut().unwrap().sort_by_key(|r| r.x1),
+            RoomSort::Rightmost => build_data.rooms.as_mut().unwrap().sort_by_key(|r| std::cmp::Reverse(r.x2)),
+            RoomSort::Topmost => build_data.rooms.as_mut().unwrap().sort_by_key(|r| r.y1),
+            RoomSort::Bottommost => build_data.rooms.as_mut().unwrap().sort_by_key(|r| std::cmp::Reverse(r.y2)),
         }
     }
 }
@@ -7,7 +7,10 @@ mod meta;
 use super::{Map, MapRect, TileType};
 use crate::{component::Position, rng};
 use initial::{empty_map::EmptyMapBuilder, rooms::RoomsBuilder};
-use meta::{borders::Borders, reveal_all::RevealAll, room_drawer::RoomDrawer, room_sorter::*};
+use meta::{
+    borders::Borders, dogleg_corridors::DoglegCorridors, door_placement::DoorPlacement, loot_spawner::LootSpawner,
+    reveal_all::RevealAll, room_drawer::RoomDrawer, room_sorter::*,
+};
 
 pub struct BuilderMap {
     pub map: Map,
@@ -15,6 +18,10 @@ pub struct BuilderMap {
     pub height: usize,
     pub rooms: Option<Vec<MapRect>>,
     pub entry: Option<Position>,
+    /// Items to spawn once the world exists: where, which item and how many.
+    pub spawn_list: Vec<(Position, String, i32)>,
+    /// The tiles dug out for each corridor, from where it leaves one room to where it enters the next.
+    pub corridors: Vec<Vec<(i32, i32)>>,
 }
 
 pub trait InitialMapBuilder {
@@ -50,6 +57,8 @@ impl BuilderChain {
                 height,
                 rooms: None,
                 entry: None,
+                spawn_list: Vec::new(),
+                corridors: Vec::new(),
             },
         }
     }
@@ -86,9 +95,13 @@ impl BuilderChain {
         self.build_data.map.clone()
     }
 
+    pub fn get_spawn_list(&mut self) -> Vec<(Position, String, i32)> {
+        std::mem::take(&mut self.build_data.spawn_list)
+    }
+
     pub fn get_dungeon_entry(&self) -> Option<Position> {
-        for ((y, x), tile) in self.build_data.map.tiles.indexed_iter() {
-            if tile.tile_type == TileType::DungeonEntry {
+        for ((y, x), tile) in self.build_data.map.tile_types().indexed_iter() {
+            if *tile == TileType::DungeonEntry {
                 return Some(Position {
                     x: x.try_into().unwrap(),
                     y: y.try_into().unwrap(),
@@ -111,6 +124,8 @@ fn empty_map_builder(builder: &mut BuilderChain) {
         builder.add(DungeonEntryRoomBased::new());
     }
 
+    builder.add(LootSpawner::new());
+
     #[cfg(debug_assertions)]
     {
         builder.add(RevealAll::new());
@@ -138,12 +153,18 @@ fn random_rooms_builder(builder: &mut BuilderChain) {
         }
     }
 
-    builder.add(RoomDrawer::new()).add(Borders::new());
+    builder
+        .add(RoomDrawer::new())
+        .add(DoglegCorridors::new())
+        .add(DoorPlacement::new())
+        .add(Borders::new());
 
     if builder.build_data.map.id == 1 {
         builder.add(DungeonEntryRoomBased::new());
     }
 
+    builder.add(LootSpawner::new());
+
     #[cfg(debug_assertions)]
     {
         builder.add(RevealAll::new());
@@ -3,53 +3,83 @@ use grid::Grid;
 use ratatui::layout::Position as RatatuiPosition;
 use ratatui::{buffer::Buffer, layout::Rect, prelude::Color, widgets::Widget};
 
-use super::Map;
+use super::{Map, TileFlags};
 use crate::component::{Position, Render};
 
-pub struct Camera {
-    /// player_pos: currently not in use!
+/// Draws the part of a map that fits on the screen. Borrows the map and the entities on it, so
+/// drawing doesn't copy them.
+pub struct Camera<'a> {
+    /// Kept in view when the map doesn't fit the screen.
     player_pos: Position,
-    map: Map,
-    entities: Vec<(Position, Render)>,
+    map: &'a Map,
+    entities: &'a [(Position, Render)],
+    /// Map coordinates which get their background colored, e.g. for targeting.
+    highlights: Vec<((i32, i32), Color)>,
 }
 
 #[allow(dead_code)]
-impl Camera {
-    pub fn new(player_pos: Position, map: Map, entities: Vec<(Position, Render)>) -> Self {
+impl<'a> Camera<'a> {
+    pub fn new(player_pos: Position, map: &'a Map, entities: &'a [(Position, Render)]) -> Self {
         Self {
             player_pos,
             map,
             entities,
+            highlights: Vec::new(),
         }
     }
 
-    pub fn set_map(&mut self, map: Map) {
+    pub fn set_highlights(&mut self, highlights: Vec<((i32, i32), Color)>) {
+        self.highlights = highlights;
+    }
+
+    pub fn set_map(&mut self, map: &'a Map) {
         self.map = map;
     }
 
-    pub fn update(&mut self, player_pos: Position, map: Map) {
+    pub fn update(&mut self, player_pos: Position, map: &'a Map) {
         self.player_pos = player_pos;
         self.map = map;
     }
 }
 
-impl Widget for Camera {
+impl Widget for Camera<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         debug_once!("Rendering map on screen area: {:?}", area);
-        let rendered_map = render_map(&self.player_pos, self.map, area, self.entities);
-        // log::debug!("{:?}", rendered_map);
-        for ((y, x), _) in rendered_map.indexed_iter() {
+        let offset = (
+            view_offset(self.player_pos.x, self.map.width, area.width),
+            view_offset(self.player_pos.y, self.map.height, area.height),
+        );
+        let mut view = render_view(self.map, self.entities, offset, area);
+        for ((x, y), color) in self.highlights {
+            let (Some(vx), Some(vy)) = ((x as usize).checked_sub(offset.0), (y as usize).checked_sub(offset.1)) else {
+                continue;
+            };
+            if let Some(tile) = view.get_mut(vy, vx) {
+                tile.bg = color;
+            }
+        }
+        for ((y, x), tile) in view.indexed_iter() {
             buf[RatatuiPosition {
-                x: x as u16,
-                y: y as u16,
+                x: area.x + x as u16,
+                y: area.y + y as u16,
             }]
-            .set_char(rendered_map[(y, x)].glyph)
-            .set_bg(rendered_map[(y, x)].bg)
-            .set_fg(rendered_map[(y, x)].fg);
+            .set_char(tile.glyph)
+            .set_bg(tile.bg)
+            .set_fg(tile.fg);
         }
     }
 }
 
+/// Where along one axis of the map the view starts. Maps which fit are shown whole, otherwise the
+/// view is centered on the player as far as the edges of the map allow.
+fn view_offset(player: i32, map_len: usize, view_len: u16) -> usize {
+    let view_len = view_len as usize;
+    if map_len <= view_len {
+        return 0;
+    }
+    (player.max(0) as usize).saturating_sub(view_len / 2).min(map_len - view_len)
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct RenderedTile {
     glyph: char,
@@ -67,15 +97,26 @@ impl Default for RenderedTile {
     }
 }
 
-pub fn render_map(_player_pos: &Position, map: Map, _area: Rect, entities: Vec<(Position, Render)>) -> Grid<RenderedTile> {
-    let mut rendered_map = Grid::init(map.height, map.width, RenderedTile::default());
+/// Renders the part of the map which fits in `area`, starting at `offset` (x, y) on the map. Only
+/// the tiles in view are looked at, so large maps cost no more to show than small ones.
+pub fn render_view(map: &Map, entities: &[(Position, Render)], offset: (usize, usize), area: Rect) -> Grid<RenderedTile> {
+    let width = map.width.saturating_sub(offset.0).min(area.width as usize);
+    let height = map.height.saturating_sub(offset.1).min(area.height as usize);
+    let mut view = Grid::init(height, width, RenderedTile::default());
+    let (types, flags) = (map.tile_types(), map.tile_flags());
 
-    for ((y, x), tile) in map.tiles.indexed_iter() {
-        if tile.tile_revealed {
-            let fg = if tile.tile_visible { Color::White } else { Color::Gray };
-            let bg = Color::Black;
-            rendered_map[(y, x)] = RenderedTile {
-                glyph: map.glyph(x, y),
+    for ((y, x), rendered) in view.indexed_iter_mut() {
+        let (mx, my) = (x + offset.0, y + offset.1);
+        let flags = flags[(my, mx)];
+        if flags.contains(TileFlags::REVEALED) {
+            let definition = map.definition(types[(my, mx)]);
+            let (fg, bg) = if flags.contains(TileFlags::VISIBLE) {
+                (definition.fg, definition.bg)
+            } else {
+                (definition.remembered_fg, definition.remembered_bg)
+            };
+            *rendered = RenderedTile {
+                glyph: definition.glyph,
                 fg,
                 bg,
             };
@@ -83,12 +124,30 @@ pub fn render_map(_player_pos: &Position, map: Map, _area: Rect, entities: Vec<(
     }
 
     for (pos, render) in entities {
-        rendered_map[(pos.y as usize, pos.x as usize)] = RenderedTile {
-            glyph: render.glyph,
-            fg: render.fg,
-            bg: render.bg,
+        let (Some(x), Some(y)) = ((pos.x as usize).checked_sub(offset.0), (pos.y as usize).checked_sub(offset.1)) else {
+            continue;
         };
+        if let Some(rendered) = view.get_mut(y, x) {
+            *rendered = RenderedTile {
+                glyph: render.glyph,
+                fg: render.fg,
+                bg: render.bg,
+            };
+        }
     }
 
-    rendered_map
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_follows_the_player_within_the_map() {
+        assert_eq!(view_offset(70, 80, 100), 0);
+        assert_eq!(view_offset(5, 80, 20), 0);
+        assert_eq!(view_offset(40, 80, 20), 30);
+        assert_eq!(view_offset(79, 80, 20), 60);
+    }
 }
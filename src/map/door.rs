@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Map, TileType};
+
+/// Something to do with a door.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DoorAction {
+    Open,
+    Close,
+}
+
+impl DoorAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DoorAction::Open => "open",
+            DoorAction::Close => "close",
+        }
+    }
+
+    /// Whether the action can be tried on the tile. Locked doors can be opened by someone with a key.
+    pub fn applies_to(&self, tile: TileType) -> bool {
+        match self {
+            DoorAction::Open => matches!(tile, TileType::DoorClosed | TileType::DoorLocked),
+            DoorAction::Close => tile == TileType::DoorOpen,
+        }
+    }
+}
+
+impl Map {
+    pub fn is_door(&self, x: i32, y: i32) -> bool {
+        matches!(
+            self.get_tile_type(x as usize, y as usize),
+            TileType::DoorOpen | TileType::DoorClosed | TileType::DoorLocked
+        )
+    }
+
+    /// Opens a closed door. Returns false if there's no closed door, or it's locked.
+    pub fn open_door(&mut self, x: i32, y: i32) -> bool {
+        if self.get_tile_type(x as usize, y as usize) != TileType::DoorClosed {
+            return false;
+        }
+        self.change_tile(x, y, TileType::DoorOpen);
+        true
+    }
+
+    /// Closes an open door. Returns false if there's no open door.
+    pub fn close_door(&mut self, x: i32, y: i32) -> bool {
+        if self.get_tile_type(x as usize, y as usize) != TileType::DoorOpen {
+            return false;
+        }
+        self.change_tile(x, y, TileType::DoorClosed);
+        true
+    }
+
+    /// Unlocks a locked door, leaving it closed. Returns false if there's no locked door.
+    pub fn unlock_door(&mut self, x: i32, y: i32) -> bool {
+        if self.get_tile_type(x as usize, y as usize) != TileType::DoorLocked {
+            return false;
+        }
+        self.change_tile(x, y, TileType::DoorClosed);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doors_open_close_and_unlock() {
+        let mut map = Map::new(1, "test", 3, 3);
+        map.define_tile(1, 1, TileType::DoorLocked);
+        map.set_revealed(1, 1, true);
+        assert!(map.is_door(1, 1) && !map.is_door(0, 0));
+        assert!(DoorAction::Open.applies_to(map.get_tile_type(1, 1)));
+
+        assert!(!map.open_door(1, 1));
+        assert!(map.unlock_door(1, 1));
+        assert!(map.open_door(1, 1));
+        assert!(map.is_walkable(1, 1) && !map.blocks_view(1, 1));
+        assert!(map.is_revealed(1, 1));
+
+        assert!(!map.open_door(1, 1));
+        assert!(map.close_door(1, 1));
+        assert!(!map.is_walkable(1, 1) && map.blocks_view(1, 1));
+        assert!(!map.close_door(1, 1));
+    }
+}
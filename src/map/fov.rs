@@ -0,0 +1,74 @@
+use grid::Grid;
+
+use super::Map;
+use crate::utils::{distance2d_pythagoras, line};
+
+/// Calculates which tiles are visible from `(x, y)` within `radius`.
+///
+/// Casts a line from the origin to every tile on the edge of the square surrounding it,
+/// and stops each line at the first tile which blocks view (that tile is still visible).
+pub fn field_of_view(map: &Map, x: i32, y: i32, radius: i32) -> Vec<(i32, i32)> {
+    let mut seen = Grid::init(map.height, map.width, false);
+    let mut visible = Vec::new();
+
+    let mut edge = Vec::new();
+    for i in -radius..=radius {
+        edge.push((x + i, y - radius));
+        edge.push((x + i, y + radius));
+        edge.push((x - radius, y + i));
+        edge.push((x + radius, y + i));
+    }
+
+    for target in edge {
+        for (tx, ty) in line((x, y), target) {
+            if !map.in_bounds(tx, ty) || distance2d_pythagoras((x, y), (tx, ty)) > radius as f32 + 0.5 {
+                break;
+            }
+            if !seen[(ty as usize, tx as usize)] {
+                seen[(ty as usize, tx as usize)] = true;
+                visible.push((tx, ty));
+            }
+            if map.blocks_view(tx as usize, ty as usize) {
+                break;
+            }
+        }
+    }
+
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+
+    fn open_map() -> Map {
+        let mut map = Map::new(1, "test", 20, 20);
+        for y in 1..19 {
+            for x in 1..19 {
+                map.define_tile(x, y, TileType::Floor);
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn fov_includes_origin_and_stays_within_radius() {
+        let map = open_map();
+        let visible = field_of_view(&map, 10, 10, 3);
+        assert!(visible.contains(&(10, 10)));
+        assert!(visible.contains(&(13, 10)));
+        assert!(!visible.contains(&(14, 10)));
+    }
+
+    #[test]
+    fn walls_block_view() {
+        let mut map = open_map();
+        for y in 1..19 {
+            map.define_tile(12, y, TileType::Wall);
+        }
+        let visible = field_of_view(&map, 10, 10, 5);
+        assert!(visible.contains(&(12, 10)));
+        assert!(!visible.contains(&(13, 10)));
+    }
+}
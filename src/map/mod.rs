@@ -1,16 +1,32 @@
 use bevy_ecs::prelude::Resource;
 use grid::Grid;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    CFG,
+    config::{TileDefinition, TilesConfig},
+};
 
 mod builders;
 pub mod camera;
+mod door;
+mod fov;
+mod pathfinding;
 mod rect;
 mod tile;
-mod tile_definition;
 pub use builders::*;
+pub use door::*;
+pub use fov::*;
+pub use pathfinding::*;
 pub use rect::*;
 pub use tile::*;
-pub use tile_definition::*;
 
 #[derive(Resource, Default)]
 pub struct Maps {
@@ -23,18 +39,41 @@ impl Maps {
     }
 }
 
+/// A level of the dungeon. The tiles are stored as separate grids of their types and flags, with
+/// everything else about a tile type looked up from its definition.
 #[derive(Default, Clone, Deserialize, Serialize)]
 pub struct Map {
     pub id: usize,
     pub name: String,
     pub width: usize,
     pub height: usize,
-    pub tiles: Grid<Tile>,
+    tile_types: Grid<TileType>,
+    tile_flags: Grid<TileFlags>,
+    /// Damage taken by destructible tiles, by position. Only tiles which took some are in here.
+    damage: HashMap<(usize, usize), i32>,
+    /// What the tiles look like and how they behave. Shared by all maps, and not saved with them.
+    #[serde(skip, default = "tile_definitions")]
+    pub definitions: Arc<TilesConfig>,
+    /// Changes whenever a tile changes, so copies of the map can tell whether they're outdated.
+    /// Unique across all maps.
+    #[serde(skip, default = "next_revision")]
+    revision: u64,
     // of course a different approach is to have walls and stuff be entities.
     // and systems which update these grids according to components
     // but then, transformations of the grids would be more complex and require transforming entities as well.
 }
 
+static REVISIONS: AtomicU64 = AtomicU64::new(1);
+
+fn next_revision() -> u64 {
+    REVISIONS.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The tile definitions from the loaded config.
+fn tile_definitions() -> Arc<TilesConfig> {
+    Arc::new(CFG.lock().unwrap().config.tiles.clone())
+}
+
 #[allow(dead_code)]
 impl Map {
     pub fn new(id: usize, name: &str, width: usize, height: usize) -> Self {
@@ -43,116 +82,245 @@ impl Map {
             name: name.into(),
             width,
             height,
-            tiles: Grid::init(height, width, WALL_TILE.clone()),
+            tile_types: Grid::init(height, width, TileType::Wall),
+            tile_flags: Grid::init(height, width, TileFlags::empty()),
+            damage: HashMap::new(),
+            definitions: tile_definitions(),
+            revision: next_revision(),
         }
     }
 
+    /// Replaces the tile definitions, e.g. when they were reloaded.
+    pub fn set_definitions(&mut self, definitions: Arc<TilesConfig>) {
+        self.definitions = definitions;
+        self.touch();
+    }
+
+    /// The definition of a tile type.
+    pub fn definition(&self, tile: TileType) -> &TileDefinition {
+        self.definitions
+            .get(tile)
+            .expect("tile definitions are completed when loaded")
+    }
+
+    /// The definition of the tile at the given position.
+    pub fn tile_definition(&self, x: usize, y: usize) -> &TileDefinition {
+        self.definition(self.get_tile_type(x, y))
+    }
+
+    /// The types of all tiles, by row and column.
+    pub fn tile_types(&self) -> &Grid<TileType> {
+        &self.tile_types
+    }
+
+    /// The flags of all tiles, by row and column.
+    pub fn tile_flags(&self) -> &Grid<TileFlags> {
+        &self.tile_flags
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    #[inline]
+    fn touch(&mut self) {
+        self.revision = next_revision();
+    }
+
     pub fn glyph(&self, x: usize, y: usize) -> char {
-        match self.get_tile_type(x, y) {
-            TileType::Floor => '.',
-            TileType::Wall => '#',
-            TileType::DungeonEntry => '§',
-        }
+        self.tile_definition(x, y).glyph
+    }
+
+    #[inline]
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
     }
 
     pub fn set_name(&mut self, name: &str) {
         self.name = name.into();
     }
 
+    /// Puts a fresh, unseen tile of the given type at the position.
     #[inline]
-    pub fn define_tile(&mut self, x: i32, y: i32, tile: Tile) {
-        self.tiles[(y as usize, x as usize)] = tile;
+    pub fn define_tile(&mut self, x: i32, y: i32, tile: TileType) {
+        let (x, y) = (x as usize, y as usize);
+        self.tile_types[(y, x)] = tile;
+        self.tile_flags[(y, x)] = TileFlags::empty();
+        self.damage.remove(&(x, y));
+        self.touch();
     }
 
-    #[inline]
-    pub fn set_tile_type(&mut self, x: i32, y: i32, tile: TileType) {
-        self.tiles[(y as usize, x as usize)].tile_type = tile;
+    /// Replaces the tile, like `define_tile`, but keeps whether it has been seen.
+    pub fn change_tile(&mut self, x: i32, y: i32, tile: TileType) {
+        let flags = self.tile_flags[(y as usize, x as usize)];
+        self.define_tile(x, y, tile);
+        self.tile_flags[(y as usize, x as usize)] = flags;
     }
 
     #[inline]
     pub fn get_tile_type(&self, x: usize, y: usize) -> TileType {
-        self.tiles[(y, x)].tile_type
+        self.tile_types[(y, x)]
     }
 
     #[inline]
-    pub fn set_revealed(&mut self, x: i32, y: i32, revealed: bool) {
-        self.tiles[(y as usize, x as usize)].tile_revealed = revealed;
+    fn set_flag(&mut self, x: i32, y: i32, flag: TileFlags, value: bool) {
+        let flags = &mut self.tile_flags[(y as usize, x as usize)];
+        if flags.contains(flag) != value {
+            flags.set(flag, value);
+            self.touch();
+        }
     }
 
     #[inline]
-    pub fn reveal_all(&mut self) {
-        self.tiles.iter_mut().for_each(|tile| tile.tile_revealed = true);
+    pub fn set_revealed(&mut self, x: i32, y: i32, revealed: bool) {
+        self.set_flag(x, y, TileFlags::REVEALED, revealed);
     }
 
     #[inline]
-    pub fn is_revealed(&self, x: usize, y: usize) -> bool {
-        self.tiles[(y, x)].tile_revealed
+    pub fn reveal_all(&mut self) {
+        self.tile_flags.iter_mut().for_each(|flags| flags.insert(TileFlags::REVEALED));
+        self.touch();
     }
 
     #[inline]
-    pub fn set_destructable(&mut self, x: i32, y: i32, destructable: bool, hitpoints: i32) {
-        self.tiles[(y as usize, x as usize)].tile_destructable = destructable;
-        self.tiles[(y as usize, x as usize)].tile_hitpoints = hitpoints;
+    pub fn is_revealed(&self, x: usize, y: usize) -> bool {
+        self.tile_flags[(y, x)].contains(TileFlags::REVEALED)
     }
 
     #[inline]
     pub fn is_destructable(&self, x: usize, y: usize) -> bool {
-        self.tiles[(y, x)].tile_destructable
+        self.tile_definition(x, y).hitpoints.is_some()
     }
 
+    /// Damage the tile can still take before it's destroyed.
     #[inline]
     pub fn get_hitpoints(&self, x: usize, y: usize) -> i32 {
-        self.tiles[(y, x)].tile_hitpoints
+        let hitpoints = self.tile_definition(x, y).hitpoints.unwrap_or(0);
+        hitpoints - self.damage.get(&(x, y)).copied().unwrap_or(0)
     }
 
-    #[inline]
-    pub fn set_walkable(&mut self, x: i32, y: i32, walkable: bool) {
-        self.tiles[(y as usize, x as usize)].tile_walkable = walkable;
+    /// Damages a destructible tile. Once it runs out of hitpoints it's replaced by what its
+    /// definition says is left of it. Returns true if the tile was destroyed.
+    pub fn damage_tile(&mut self, x: i32, y: i32, damage: i32) -> bool {
+        if !self.is_destructable(x as usize, y as usize) {
+            return false;
+        }
+        *self.damage.entry((x as usize, y as usize)).or_default() += damage;
+        if self.get_hitpoints(x as usize, y as usize) > 0 {
+            return false;
+        }
+        let remains = self
+            .tile_definition(x as usize, y as usize)
+            .destroyed
+            .unwrap_or(TileType::Floor);
+        self.change_tile(x, y, remains);
+        true
     }
 
     #[inline]
     pub fn is_walkable(&self, x: i32, y: i32) -> bool {
-        self.tiles[(y as usize, x as usize)].tile_walkable
+        self.tile_definition(x as usize, y as usize).walkable
     }
 
     #[inline]
     pub fn set_blocked(&mut self, x: i32, y: i32, blocked: bool) {
-        self.tiles[(y as usize, x as usize)].tile_blocked = blocked;
+        self.set_flag(x, y, TileFlags::BLOCKED, blocked);
     }
 
     #[inline]
     pub fn is_blocked(&self, x: usize, y: usize) -> bool {
-        self.tiles[(y, x)].tile_blocked
-    }
-
-    #[inline]
-    pub fn set_blocks_view(&mut self, x: i32, y: i32, blocks_view: bool) {
-        self.tiles[(y as usize, x as usize)].tile_blocks_view = blocks_view;
+        self.tile_flags[(y, x)].contains(TileFlags::BLOCKED)
     }
 
     #[inline]
     pub fn blocks_view(&self, x: usize, y: usize) -> bool {
-        self.tiles[(y, x)].tile_blocks_view
+        self.tile_definition(x, y).blocks_view
     }
 
     #[inline]
     pub fn set_visible(&mut self, x: i32, y: i32, visible: bool) {
-        self.tiles[(y as usize, x as usize)].tile_visible = visible;
+        self.set_flag(x, y, TileFlags::VISIBLE, visible);
     }
 
     #[inline]
     pub fn is_visible(&self, x: usize, y: usize) -> bool {
-        self.tiles[(y, x)].tile_visible
-    }
-
-    // pub fn insert_col(&mut self, i: usize, h: usize) {
-    //     self.tile_type.insert_col(i, vec![TileType::default(); h]);
-    //     self.tile_revealed.insert_col(i, vec![true; h]);
-    //     self.tile_visible.insert_col(i, vec![false; h]);
-    //     self.tile_blocked.insert_col(i, vec![false; h]);
-    //     self.tile_blocks_view.insert_col(i, vec![true; h]);
-    //     self.tile_walkable.insert_col(i, vec![false; h]);
-    //     self.tile_destructable.insert_col(i, vec![false; h]);
-    //     self.tile_hitpoints.insert_col(i, vec![0; h]);
-    // }
+        self.tile_flags[(y, x)].contains(TileFlags::VISIBLE)
+    }
+
+    #[inline]
+    pub fn clear_visible(&mut self) {
+        self.tile_flags.iter_mut().for_each(|flags| flags.remove(TileFlags::VISIBLE));
+        self.touch();
+    }
+
+    /// Adds a column of fresh tiles of the given type, before the column at `index`.
+    pub fn insert_col(&mut self, index: usize, tile: TileType) {
+        self.tile_types.insert_col(index, vec![tile; self.height]);
+        self.tile_flags.insert_col(index, vec![TileFlags::empty(); self.height]);
+        self.damage = self
+            .damage
+            .drain()
+            .map(|((x, y), d)| ((if x >= index { x + 1 } else { x }, y), d))
+            .collect();
+        self.width += 1;
+        self.touch();
+    }
+
+    /// Adds a row of fresh tiles of the given type, before the row at `index`.
+    pub fn insert_row(&mut self, index: usize, tile: TileType) {
+        self.tile_types.insert_row(index, vec![tile; self.width]);
+        self.tile_flags.insert_row(index, vec![TileFlags::empty(); self.width]);
+        self.damage = self
+            .damage
+            .drain()
+            .map(|((x, y), d)| ((x, if y >= index { y + 1 } else { y }), d))
+            .collect();
+        self.height += 1;
+        self.touch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destroyed_tiles_become_passable() {
+        let mut map = Map::new(1, "test", 3, 3);
+        map.define_tile(1, 1, TileType::DoorClosed);
+        map.set_revealed(0, 0, true);
+
+        assert!(!map.damage_tile(0, 0, map.get_hitpoints(0, 0) - 1));
+        assert!(map.damage_tile(0, 0, 1));
+        assert_eq!(map.get_tile_type(0, 0), TileType::Rubble);
+        assert!(map.is_walkable(0, 0) && !map.blocks_view(0, 0) && map.is_revealed(0, 0));
+        assert!(!map.damage_tile(0, 0, 100));
+
+        assert!(map.damage_tile(1, 1, map.get_hitpoints(1, 1)));
+        assert_eq!(map.get_tile_type(1, 1), TileType::Floor);
+    }
+
+    #[test]
+    fn revision_changes_with_the_tiles() {
+        let mut map = Map::new(1, "test", 3, 3);
+        let copy = map.clone();
+        map.set_visible(1, 1, false);
+        assert_eq!(map.revision(), copy.revision());
+        map.set_visible(1, 1, true);
+        assert_ne!(map.revision(), copy.revision());
+        assert_ne!(Map::new(1, "test", 3, 3).revision(), copy.revision());
+    }
+
+    #[test]
+    fn inserted_rows_and_columns_keep_tiles_in_place() {
+        let mut map = Map::new(1, "test", 3, 3);
+        map.define_tile(1, 1, TileType::Floor);
+        map.damage_tile(2, 2, 5);
+        map.insert_col(0, TileType::Border);
+        map.insert_row(0, TileType::Border);
+        assert_eq!((map.width, map.height), (4, 4));
+        assert_eq!(map.get_tile_type(0, 0), TileType::Border);
+        assert_eq!(map.get_tile_type(2, 2), TileType::Floor);
+        assert_eq!(map.get_hitpoints(3, 3), map.definition(TileType::Wall).hitpoints.unwrap() - 5);
+    }
 }
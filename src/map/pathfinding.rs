@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use grid::Grid;
+
+use super::{Map, TileType};
+
+const NEIGHBOURS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Finds the first step towards the nearest reachable tile which borders unrevealed territory.
+///
+/// Only revealed, walkable tiles (and closed doors) are searched, so the path never leads through
+/// unknown terrain.
+/// Returns the relative step `(dx, dy)`, or `None` if there is nothing left to explore.
+pub fn explore_step(map: &Map, x: i32, y: i32) -> Option<(i32, i32)> {
+    let mut came_from: Grid<Option<(i32, i32)>> = Grid::init(map.height, map.width, None);
+    let mut queue = VecDeque::new();
+
+    came_from[(y as usize, x as usize)] = Some((x, y));
+    queue.push_back((x, y));
+
+    while let Some((cx, cy)) = queue.pop_front() {
+        if (cx, cy) != (x, y) && borders_unrevealed(map, cx, cy) {
+            return Some(first_step(&came_from, (x, y), (cx, cy)));
+        }
+
+        for (dx, dy) in NEIGHBOURS {
+            let (nx, ny) = (cx + dx, cy + dy);
+            if !map.in_bounds(nx, ny) || came_from[(ny as usize, nx as usize)].is_some() {
+                continue;
+            }
+            // Closed doors are opened by walking into them.
+            let passable = map.is_walkable(nx, ny) || map.get_tile_type(nx as usize, ny as usize) == TileType::DoorClosed;
+            if map.is_revealed(nx as usize, ny as usize) && passable {
+                came_from[(ny as usize, nx as usize)] = Some((cx, cy));
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    None
+}
+
+fn borders_unrevealed(map: &Map, x: i32, y: i32) -> bool {
+    NEIGHBOURS
+        .iter()
+        .any(|(dx, dy)| map.in_bounds(x + dx, y + dy) && !map.is_revealed((x + dx) as usize, (y + dy) as usize))
+}
+
+fn first_step(came_from: &Grid<Option<(i32, i32)>>, start: (i32, i32), goal: (i32, i32)) -> (i32, i32) {
+    let mut current = goal;
+    while let Some(previous) = came_from[(current.1 as usize, current.0 as usize)] {
+        if previous == start {
+            break;
+        }
+        current = previous;
+    }
+    (current.0 - start.0, current.1 - start.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corridor() -> Map {
+        let mut map = Map::new(1, "test", 10, 3);
+        for x in 1..9 {
+            map.define_tile(x, 1, TileType::Floor);
+        }
+        map
+    }
+
+    #[test]
+    fn explore_moves_towards_unrevealed_tiles() {
+        let mut map = corridor();
+        for x in 0..5 {
+            for y in 0..3 {
+                map.set_revealed(x, y, true);
+            }
+        }
+        assert_eq!(explore_step(&map, 1, 1), Some((1, 0)));
+    }
+
+    #[test]
+    fn nothing_to_explore_when_all_revealed() {
+        let mut map = corridor();
+        map.reveal_all();
+        assert_eq!(explore_step(&map, 1, 1), None);
+    }
+}
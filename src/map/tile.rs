@@ -1,36 +1,44 @@
+use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, PartialEq, Eq, Hash, Clone, Copy, Deserialize, Serialize)]
+/// The kinds of tiles. What they look like and how they behave is defined in the tiles data file.
+/// Their order is that of the table of tile definitions.
+#[derive(Default, PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize, Serialize)]
+#[repr(u8)]
 pub enum TileType {
     #[default]
     Wall,
+    /// Wall around the edge of a map, which can't be destroyed.
+    Border,
     Floor,
     DungeonEntry,
+    DoorOpen,
+    DoorClosed,
+    DoorLocked,
+    Rubble,
 }
 
-#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
-pub struct Tile {
-    pub tile_type: TileType,
-    pub tile_revealed: bool,
-    pub tile_visible: bool,
-    pub tile_blocked: bool,
-    pub tile_blocks_view: bool,
-    pub tile_walkable: bool,
-    pub tile_destructable: bool,
-    pub tile_hitpoints: i32,
+impl TileType {
+    pub const ALL: [TileType; 8] = [
+        TileType::Wall,
+        TileType::Border,
+        TileType::Floor,
+        TileType::DungeonEntry,
+        TileType::DoorOpen,
+        TileType::DoorClosed,
+        TileType::DoorLocked,
+        TileType::Rubble,
+    ];
 }
 
-impl Default for Tile {
-    fn default() -> Self {
-        Tile {
-            tile_type: TileType::default(),
-            tile_revealed: false,
-            tile_visible: false,
-            tile_blocked: false,
-            tile_blocks_view: true,
-            tile_walkable: false,
-            tile_destructable: false,
-            tile_hitpoints: 0,
-        }
+bitflags! {
+    /// What has happened to a tile of a map, as opposed to what kind of tile it is.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct TileFlags: u8 {
+        /// Has been seen by the player.
+        const REVEALED = 1;
+        /// Is in the player's view.
+        const VISIBLE = 1 << 1;
+        const BLOCKED = 1 << 2;
     }
 }
@@ -11,6 +11,10 @@ pub struct PlayerBundle {
     position: Position, // Add more components here as needed
     speed: Speed,
     energy: Energy,
+    viewshed: Viewshed,
+    regeneration: Regeneration,
+    skills: Skills,
+    known_spells: KnownSpells,
     // markers
     sentient: Sentient,
     corporeal: Corporeal,
@@ -86,6 +90,24 @@ impl PlayerBundle {
             },
             position: pos,
             speed: Speed { speed: 2.0 },
+            viewshed: Viewshed {
+                visible_tiles: Vec::new(),
+                range: cfg.config.player.vision_range,
+                dirty: true,
+            },
+            regeneration: Regeneration {
+                hp: Regen::from(&cfg.config.player.hp_regen),
+                mp: Regen::from(&cfg.config.player.mp_regen),
+            },
+            skills: Skills {
+                melee: cfg.config.player.skills.melee,
+                defense: cfg.config.player.skills.defense,
+                magic: cfg.config.player.skills.magic,
+                unspent: 0,
+            },
+            known_spells: KnownSpells {
+                spells: cfg.config.player.spells.clone(),
+            },
             // Markers
             energy: Energy { energy: 0 }, // this one just exists to simply intent system
             sentient: Sentient,
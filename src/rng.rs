@@ -1,5 +1,5 @@
-use bevy::ecs::resource::Resource;
-use bracket_random::prelude::RandomNumberGenerator;
+use bevy::{ecs::resource::Resource, log::error};
+use bracket_random::prelude::{DiceType, RandomNumberGenerator};
 use std::sync::Mutex;
 
 lazy_static! {
@@ -18,15 +18,42 @@ pub fn roll_dice(n: i32, t: i32) -> i32 {
     RNG.lock().unwrap().roll_dice(n, t)
 }
 
-// TODO: improve this
+/// Rolls a dice string like `3d6+2`. Dice strings in the config are validated when it's loaded,
+/// so a bad one here is logged and rolls 0.
 pub fn roll_str<T: ToString>(dice: T) -> i32 {
-    let result = RNG.lock().unwrap().roll_str(dice);
-    if let Ok(result) = result {
-        result
-    } else {
-        println!("{:?}", result);
-        0
+    match parse_dice(&dice.to_string()) {
+        Ok(dice) => RNG.lock().unwrap().roll(dice),
+        Err(e) => {
+            error!("{e}");
+            0
+        }
+    }
+}
+
+/// Parses a dice string like `1d20`, `3d6+2` or `2d4-1`. Unlike bracket-random's parser, the whole
+/// string has to match, so `5d10+5x` is an error rather than `5d10+5`.
+pub fn parse_dice(dice: &str) -> Result<DiceType, String> {
+    let invalid = || format!("`{dice}` is not a dice string like `3d6+2`");
+    let number = |s: &str| {
+        (!s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+            .then(|| s.parse::<i32>().ok())
+            .flatten()
+            .ok_or_else(invalid)
+    };
+    let trimmed: String = dice.split_whitespace().collect();
+    let (n_dice, rest) = trimmed.split_once('d').ok_or_else(invalid)?;
+    let (die_type, bonus) = match rest.split_once('+') {
+        Some((die_type, bonus)) => (die_type, number(bonus)?),
+        None => match rest.split_once('-') {
+            Some((die_type, bonus)) => (die_type, -number(bonus)?),
+            None => (rest, 0),
+        },
+    };
+    let (n_dice, die_type) = (number(n_dice)?, number(die_type)?);
+    if n_dice == 0 || die_type == 0 {
+        return Err(format!("`{dice}` rolls no dice"));
     }
+    Ok(DiceType::new(n_dice, die_type, bonus))
 }
 
 pub fn range(min: i32, max: i32) -> i32 {
@@ -58,6 +85,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dice_strings_must_match_whole() {
+        assert_eq!(parse_dice("5d10+5"), Ok(DiceType::new(5, 10, 5)));
+        assert_eq!(parse_dice("2d4 - 1"), Ok(DiceType::new(2, 4, -1)));
+        assert_eq!(parse_dice("1d20"), Ok(DiceType::new(1, 20, 0)));
+        for bad in ["5d10+5x", "d6", "3d", "3d6+", "0d6", "3x6", "", "1d6+-1"] {
+            assert!(parse_dice(bad).is_err(), "{bad}");
+        }
+    }
+
     #[test]
     fn roll_str_1d10() {
         for _ in 1..=100 {
@@ -0,0 +1,427 @@
+use bevy_ecs::{
+    component::{HookContext, Mutable},
+    prelude::*,
+    world::DeferredWorld,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::component::{Attributes, Speed, Stats, Viewshed};
+
+/// Changes a status effect makes to its entity while it is present.
+/// Applied when the effect is added, and reverted when it is removed.
+pub struct StatusModifiers {
+    pub str: i32,
+    pub dex: i32,
+    pub con: i32,
+    pub int: i32,
+    /// Multiplies the entity's `Speed`. Remember that a higher speed means actions cost more energy.
+    pub speed: f32,
+    /// Limits the entity's view to adjacent tiles.
+    pub blinds: bool,
+}
+
+impl StatusModifiers {
+    pub const NONE: StatusModifiers = StatusModifiers {
+        str: 0,
+        dex: 0,
+        con: 0,
+        int: 0,
+        speed: 1.0,
+        blinds: false,
+    };
+}
+
+/// Shared behaviour of temporary status effects.
+/// Each status effect is a component with a duration counted in game ticks.
+pub trait StatusEffect: Component<Mutability = Mutable> + Clone {
+    const NAME: &'static str;
+    const MODIFIERS: StatusModifiers = StatusModifiers::NONE;
+
+    fn duration(&self) -> u64;
+
+    fn duration_mut(&mut self) -> &mut u64;
+
+    /// Combines a newly applied effect with the one already present.
+    /// By default the duration is refreshed, keeping whichever is longer.
+    fn stack(&mut self, other: &Self) {
+        *self.duration_mut() = self.duration().max(other.duration());
+    }
+
+    /// Called once for every game tick the effect is present.
+    fn tick(&self, _stats: &mut Stats) {}
+
+    /// Message shown to the player when the effect wears off.
+    fn expire_message(&self) -> &'static str;
+}
+
+/// Applies a status effect to an entity, stacking it with the same effect if it is already present.
+pub fn apply_status<T: StatusEffect>(commands: &mut Commands, entity: Entity, status: T) {
+    let new = status.clone();
+    commands
+        .entity(entity)
+        .entry::<T>()
+        .and_modify(move |mut existing| existing.stack(&new))
+        .or_insert(status);
+}
+
+fn status_added<T: StatusEffect>(mut world: DeferredWorld, context: HookContext) {
+    apply_modifiers(&mut world, context.entity, &T::MODIFIERS, 1);
+}
+
+fn status_removed<T: StatusEffect>(mut world: DeferredWorld, context: HookContext) {
+    apply_modifiers(&mut world, context.entity, &T::MODIFIERS, -1);
+}
+
+fn apply_modifiers(world: &mut DeferredWorld, entity: Entity, modifiers: &StatusModifiers, sign: i32) {
+    if let Some(mut attributes) = world.get_mut::<Attributes>(entity) {
+        attributes.str.modifiers += sign * modifiers.str;
+        attributes.dex.modifiers += sign * modifiers.dex;
+        attributes.con.modifiers += sign * modifiers.con;
+        attributes.int.modifiers += sign * modifiers.int;
+    }
+    if modifiers.speed != 1.0
+        && let Some(mut speed) = world.get_mut::<Speed>(entity)
+    {
+        if sign > 0 {
+            speed.speed *= modifiers.speed;
+        } else {
+            speed.speed /= modifiers.speed;
+        }
+    }
+    if modifiers.blinds
+        && let Some(mut viewshed) = world.get_mut::<Viewshed>(entity)
+    {
+        viewshed.dirty = true;
+    }
+}
+
+//----------------//
+// Status effects //
+//----------------//
+
+/// Loses `damage` hit points every tick. Stacking intensifies the poison.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Poisoned>, on_remove = status_removed::<Poisoned>)]
+pub struct Poisoned {
+    pub duration: u64,
+    pub damage: i32,
+}
+
+impl StatusEffect for Poisoned {
+    const NAME: &'static str = "poisoned";
+    const MODIFIERS: StatusModifiers = StatusModifiers {
+        str: -5,
+        con: -5,
+        ..StatusModifiers::NONE
+    };
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn stack(&mut self, other: &Self) {
+        self.duration = self.duration.max(other.duration);
+        self.damage += other.damage;
+    }
+
+    fn tick(&self, stats: &mut Stats) {
+        stats.hp.decrease(self.damage);
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "You feel less sick."
+    }
+}
+
+/// Moves in random directions some of the time.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Confused>, on_remove = status_removed::<Confused>)]
+pub struct Confused {
+    pub duration: u64,
+}
+
+impl StatusEffect for Confused {
+    const NAME: &'static str = "confused";
+    const MODIFIERS: StatusModifiers = StatusModifiers {
+        int: -10,
+        ..StatusModifiers::NONE
+    };
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "You feel less confused."
+    }
+}
+
+/// Actions take twice as long.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Slowed>, on_remove = status_removed::<Slowed>)]
+pub struct Slowed {
+    pub duration: u64,
+}
+
+impl StatusEffect for Slowed {
+    const NAME: &'static str = "slowed";
+    const MODIFIERS: StatusModifiers = StatusModifiers {
+        dex: -5,
+        speed: 2.0,
+        ..StatusModifiers::NONE
+    };
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "You feel yourself speed up."
+    }
+}
+
+/// Actions take half as long.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Hasted>, on_remove = status_removed::<Hasted>)]
+pub struct Hasted {
+    pub duration: u64,
+}
+
+impl StatusEffect for Hasted {
+    const NAME: &'static str = "hasted";
+    const MODIFIERS: StatusModifiers = StatusModifiers {
+        dex: 5,
+        speed: 0.5,
+        ..StatusModifiers::NONE
+    };
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "You feel yourself slow down."
+    }
+}
+
+/// Can only see adjacent tiles.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Blinded>, on_remove = status_removed::<Blinded>)]
+pub struct Blinded {
+    pub duration: u64,
+}
+
+impl StatusEffect for Blinded {
+    const NAME: &'static str = "blinded";
+    const MODIFIERS: StatusModifiers = StatusModifiers {
+        dex: -10,
+        blinds: true,
+        ..StatusModifiers::NONE
+    };
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "You can see again."
+    }
+}
+
+/// Regains `amount` hit points every tick. Stacking extends the duration.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Regenerating>, on_remove = status_removed::<Regenerating>)]
+pub struct Regenerating {
+    pub duration: u64,
+    pub amount: i32,
+}
+
+impl StatusEffect for Regenerating {
+    const NAME: &'static str = "regenerating";
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn stack(&mut self, other: &Self) {
+        self.duration += other.duration;
+        self.amount = self.amount.max(other.amount);
+    }
+
+    fn tick(&self, stats: &mut Stats) {
+        stats.hp.increase(self.amount);
+    }
+
+    fn expire_message(&self) -> &'static str {
+        "Your body stops regenerating."
+    }
+}
+
+/// Can't act at all. Paralysis can't be extended while it lasts.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[component(on_add = status_added::<Paralysed>, on_remove = status_removed::<Paralysed>)]
+pub struct Paralysed {
+    pub duration: u64,
+}
+
+impl StatusEffect for Paralysed {
+    const NAME: &'static str = "paralysed";
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn duration_mut(&mut self) -> &mut u64 {
+        &mut self.duration
+    }
+
+    fn stack(&mut self, _other: &Self) {}
+
+    fn expire_message(&self) -> &'static str {
+        "You can move again."
+    }
+}
+
+/// Names a status effect in data files, e.g. as the effect of a spell or item.
+/// Poison deals, and regeneration heals, one hit point per tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusKind {
+    Poisoned,
+    Confused,
+    Slowed,
+    Hasted,
+    Blinded,
+    Regenerating,
+    Paralysed,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Poisoned => Poisoned::NAME,
+            StatusKind::Confused => Confused::NAME,
+            StatusKind::Slowed => Slowed::NAME,
+            StatusKind::Hasted => Hasted::NAME,
+            StatusKind::Blinded => Blinded::NAME,
+            StatusKind::Regenerating => Regenerating::NAME,
+            StatusKind::Paralysed => Paralysed::NAME,
+        }
+    }
+
+    /// Applies the status effect to `entity` for `duration` ticks.
+    pub fn apply(&self, commands: &mut Commands, entity: Entity, duration: u64) {
+        match self {
+            StatusKind::Poisoned => apply_status(commands, entity, Poisoned { duration, damage: 1 }),
+            StatusKind::Confused => apply_status(commands, entity, Confused { duration }),
+            StatusKind::Slowed => apply_status(commands, entity, Slowed { duration }),
+            StatusKind::Hasted => apply_status(commands, entity, Hasted { duration }),
+            StatusKind::Blinded => apply_status(commands, entity, Blinded { duration }),
+            StatusKind::Regenerating => apply_status(commands, entity, Regenerating { duration, amount: 1 }),
+            StatusKind::Paralysed => apply_status(commands, entity, Paralysed { duration }),
+        }
+    }
+}
+
+/// Returns the names and remaining durations of all status effects on an entity.
+pub fn status_names(entity: &EntityRef) -> Vec<(&'static str, u64)> {
+    fn push<T: StatusEffect>(entity: &EntityRef, names: &mut Vec<(&'static str, u64)>) {
+        if let Some(status) = entity.get::<T>() {
+            names.push((T::NAME, status.duration()));
+        }
+    }
+
+    let mut names = Vec::new();
+    push::<Poisoned>(entity, &mut names);
+    push::<Confused>(entity, &mut names);
+    push::<Slowed>(entity, &mut names);
+    push::<Hasted>(entity, &mut names);
+    push::<Blinded>(entity, &mut names);
+    push::<Regenerating>(entity, &mut names);
+    push::<Paralysed>(entity, &mut names);
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Attribute;
+
+    #[test]
+    fn default_stacking_refreshes_to_longest_duration() {
+        let mut confused = Confused { duration: 5 };
+        confused.stack(&Confused { duration: 3 });
+        assert_eq!(confused.duration, 5);
+        confused.stack(&Confused { duration: 8 });
+        assert_eq!(confused.duration, 8);
+    }
+
+    #[test]
+    fn poison_stacking_intensifies() {
+        let mut poison = Poisoned { duration: 5, damage: 1 };
+        poison.stack(&Poisoned { duration: 3, damage: 2 });
+        assert_eq!(poison.duration, 5);
+        assert_eq!(poison.damage, 3);
+    }
+
+    #[test]
+    fn regeneration_stacking_extends_duration() {
+        let mut regen = Regenerating { duration: 5, amount: 1 };
+        regen.stack(&Regenerating { duration: 5, amount: 2 });
+        assert_eq!(regen.duration, 10);
+        assert_eq!(regen.amount, 2);
+    }
+
+    #[test]
+    fn paralysis_does_not_stack() {
+        let mut paralysed = Paralysed { duration: 2 };
+        paralysed.stack(&Paralysed { duration: 10 });
+        assert_eq!(paralysed.duration, 2);
+    }
+
+    #[test]
+    fn modifiers_are_reverted_on_removal() {
+        let mut world = World::new();
+        let attributes = Attributes {
+            str: Attribute {
+                base: 50,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let entity = world.spawn((attributes, Speed { speed: 1.0 })).id();
+
+        world.entity_mut(entity).insert(Slowed { duration: 3 });
+        world.entity_mut(entity).insert(Poisoned { duration: 3, damage: 1 });
+        assert_eq!(world.get::<Speed>(entity).unwrap().speed, 2.0);
+        assert_eq!(world.get::<Attributes>(entity).unwrap().str.value(), 45);
+
+        world.entity_mut(entity).remove::<Slowed>();
+        world.entity_mut(entity).remove::<Poisoned>();
+        assert_eq!(world.get::<Speed>(entity).unwrap().speed, 1.0);
+        assert_eq!(world.get::<Attributes>(entity).unwrap().str.value(), 50);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    // TurnState,
+    TurnState,
     component::{Energy, PerformAction, Position, SpendEnergy},
 };
 use bevy::prelude::*;
@@ -16,6 +16,17 @@ pub fn movement_action_system(mut query: Query<(&mut Position, &PerformAction)>)
                 position.x += dx;
                 position.y += dy;
             }
+            PerformAction::MeleeAttack { .. }
+            | PerformAction::Throw { .. }
+            | PerformAction::Fire { .. }
+            | PerformAction::UseItem { .. }
+            | PerformAction::Equip { .. }
+            | PerformAction::Unequip { .. }
+            | PerformAction::Drop { .. }
+            | PerformAction::PickUp
+            | PerformAction::OperateDoor { .. }
+            | PerformAction::Bash { .. }
+            | PerformAction::Cast { .. } => {}
         }
     }
 }
@@ -26,3 +37,8 @@ pub fn spend_energy_system(query: Query<(&mut Energy, &SpendEnergy)>) {
         energy.energy -= spend.0;
     }
 }
+
+/// Hands the turn back to the player once the other entities have acted.
+pub fn end_npc_turn_system(mut next_state: ResMut<NextState<TurnState>>) {
+    next_state.set(TurnState::PlayersTurn);
+}
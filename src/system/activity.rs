@@ -0,0 +1,51 @@
+use crate::{
+    CurrentGameData,
+    component::{Activity, Enemy, Intent, Player, Position, Stats},
+    gamelog::GameLog,
+    map::explore_step,
+};
+use bevy::prelude::*;
+
+/// Turns the player's current activity into an intent for this turn,
+/// or ends the activity if it is finished or has been interrupted.
+#[allow(clippy::type_complexity)]
+pub fn activity_system(
+    cgd: Res<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    player_query: Query<(Entity, &Activity, &Stats, &Position), (With<Player>, Without<Intent>)>,
+    enemy_query: Query<(&Position, &Name), With<Enemy>>,
+) {
+    let Ok((entity, activity, stats, pos)) = player_query.single() else {
+        return;
+    };
+    let map = &cgd.maps.map[pos.map];
+
+    if let Some((_, name)) = enemy_query
+        .iter()
+        .find(|(p, _)| p.map == pos.map && map.is_visible(p.x as usize, p.y as usize))
+    {
+        log.add(format!("You stop {} - {} comes into view.", activity.description(), name));
+        commands.entity(entity).remove::<Activity>();
+        return;
+    }
+
+    match activity {
+        Activity::Resting => {
+            if stats.hp.is_full() && stats.mp.is_full() {
+                log.add("You feel rested.");
+                commands.entity(entity).remove::<Activity>();
+            } else {
+                commands.entity(entity).insert(Intent::Nothing);
+            }
+        }
+        Activity::AutoExploring => {
+            if let Some((dx, dy)) = explore_step(map, pos.x, pos.y) {
+                commands.entity(entity).insert(Intent::MoveRelative { dx, dy });
+            } else {
+                log.add("There is nothing left to explore.");
+                commands.entity(entity).remove::<Activity>();
+            }
+        }
+    }
+}
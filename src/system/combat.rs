@@ -0,0 +1,351 @@
+use crate::{
+    CFG, CurrentGameData,
+    component::{Attributes, DetailedName, PerformAction, Player, Position, Skills, Stats},
+    effect::EffectEvent,
+    game_event::GameEvent,
+    gamelog::{GameLog, capitalize, display_name, verb},
+    gamelogic::{CheckModifiers, RollMode, RollResult, RollResultType, Winner, opposed_roll},
+    item::{CarriedBy, Charges, Item, KnownItems, LootDropEvent, Stack, consume},
+    map::Map,
+    rng,
+    status::{Blinded, Paralysed},
+    system::equipment::{EquippedItems, wielded_item, wielded_weapon},
+    targeting::{TargetingPurpose, line_of_fire},
+};
+use bevy::prelude::*;
+
+type Defenders<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Attributes,
+        Option<&'static Skills>,
+        &'static Position,
+        Has<Paralysed>,
+    ),
+>;
+type Combatants<'w, 's> = Query<'w, 's, (&'static mut Stats, Option<&'static Name>, Has<Player>)>;
+type Occupants<'w, 's> = Query<'w, 's, (Entity, &'static Position), With<Stats>>;
+
+/// A single attack, resolved by `resolve_attack`.
+struct Attack<'a> {
+    attacker: Entity,
+    target: Entity,
+    attributes: &'a Attributes,
+    skill: i32,
+    /// Bonus skill points for hitting, from the weapon.
+    to_hit: i32,
+    blinded: bool,
+    damage: &'a str,
+}
+
+/// Resolves melee attacks as an opposed roll of the attacker's dexterity and melee skill against
+/// the defender's dexterity and defense skill. The defender's defense value counts as extra defense
+/// skill, and the protection value is taken off the damage. Blind attackers roll with disadvantage
+/// and attacks against paralysed targets with advantage. Killing something awards experience to the
+/// attacker.
+#[allow(clippy::type_complexity)]
+pub fn melee_combat_system(
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    mut loot: EventWriter<LootDropEvent>,
+    attackers: Query<(Entity, &PerformAction, &Attributes, Option<&Skills>, Has<Blinded>)>,
+    equipped: EquippedItems,
+    defenders: Defenders,
+    mut combatants: Combatants,
+) {
+    for (attacker, action, attributes, skills, blinded) in attackers.iter() {
+        let PerformAction::MeleeAttack { target } = *action else {
+            continue;
+        };
+        let (damage, to_hit) = wielded_weapon(attacker, &equipped).unwrap_or(("1d4".to_string(), 0));
+        let attack = Attack {
+            attacker,
+            target,
+            attributes,
+            skill: skills.map_or(0, |s| s.melee),
+            to_hit,
+            blinded,
+            damage: &damage,
+        };
+        resolve_attack(&mut log, &mut commands, &mut loot, &defenders, &mut combatants, attack);
+    }
+}
+
+/// Resolves thrown items. The item follows the line of fire towards the targeted tile and
+/// attacks the first thing standing in its way. Items with effects on impact (e.g. explosives)
+/// set them off around the tile where they land. One item of the stack is used up.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn throw_system(
+    cgd: Res<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    known: Res<KnownItems>,
+    mut commands: Commands,
+    mut effects: EventWriter<EffectEvent>,
+    mut loot: EventWriter<LootDropEvent>,
+    throwers: Query<(Entity, &PerformAction, &Position, &Attributes, Has<Player>, Has<Blinded>)>,
+    mut items: Query<(&Item, &DetailedName, Option<&mut Stack>, Option<&mut Charges>)>,
+    occupants: Occupants,
+    defenders: Defenders,
+    mut combatants: Combatants,
+) {
+    for (thrower, action, pos, attributes, is_player, blinded) in throwers.iter() {
+        let PerformAction::Throw { item, x, y } = *action else {
+            continue;
+        };
+        let Ok((item_data, name, stack, charges)) = items.get_mut(item) else {
+            continue;
+        };
+        let Some(throw) = item_data.definition().and_then(|d| d.throw) else {
+            continue;
+        };
+        let name = known.shown_name(item_data, name).to_string();
+        consume(&mut commands, item, stack, charges);
+
+        if is_player {
+            log.add(format!("You throw the {}.", name));
+        }
+        let (impact, hit) = projectile_impact(&cgd.maps.map[pos.map], &occupants, thrower, pos, (x, y));
+        match hit {
+            Some(target) => {
+                let attack = Attack {
+                    attacker: thrower,
+                    target,
+                    attributes,
+                    skill: 0,
+                    to_hit: 0,
+                    blinded,
+                    damage: &throw.damage,
+                };
+                resolve_attack(&mut log, &mut commands, &mut loot, &defenders, &mut combatants, attack);
+            }
+            None if is_player => log.add(format!("The {} hits nothing.", name)),
+            None => {}
+        }
+
+        if let Some(message) = throw.message.as_ref() {
+            log.add(message.clone());
+        }
+        let map = &cgd.maps.map[pos.map];
+        for (tx, ty) in throw.area.tiles(map, impact, impact) {
+            for effect in throw.effects.iter() {
+                effects.write(EffectEvent {
+                    source: thrower,
+                    effect: effect.clone(),
+                    pos: Position {
+                        x: tx,
+                        y: ty,
+                        map: pos.map,
+                    },
+                });
+            }
+        }
+    }
+}
+
+/// Handles the fire command by finding the wielded launcher and something for it to fire, and
+/// asking the player for a target.
+pub fn fire_prompt_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut log: ResMut<GameLog>,
+    player: Query<Entity, With<Player>>,
+    equipped: EquippedItems,
+    carried: Query<(Entity, &Item, &CarriedBy)>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        if *event != GameEvent::FireWeapon {
+            continue;
+        }
+        let Some((weapon, definition)) = wielded_item(player, &equipped) else {
+            log.add("You aren't wielding anything to fire.");
+            continue;
+        };
+        let Some(launcher) = definition.equipment.and_then(|e| e.launcher) else {
+            log.add(format!("You can't fire the {}.", definition.name));
+            continue;
+        };
+        let Some((ammo, ..)) = carried
+            .iter()
+            .find(|(_, item, carrier)| carrier.0 == player && item.name == launcher.ammo)
+        else {
+            log.add(format!("You have no {}s to fire.", launcher.ammo));
+            continue;
+        };
+        events_to_send.push(GameEvent::StartTargeting(TargetingPurpose::Fire { weapon, ammo }));
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+}
+
+/// Resolves shots from launchers. The ammo follows the line of fire like a thrown item and attacks
+/// the first thing standing in its way, with the launcher's damage and to-hit bonus. One piece of
+/// ammo is used up.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn fire_system(
+    cgd: Res<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    known: Res<KnownItems>,
+    mut commands: Commands,
+    mut loot: EventWriter<LootDropEvent>,
+    shooters: Query<(Entity, &PerformAction, &Position, &Attributes, Has<Player>, Has<Blinded>)>,
+    mut items: Query<(&Item, &DetailedName, Option<&mut Stack>, Option<&mut Charges>)>,
+    occupants: Occupants,
+    defenders: Defenders,
+    mut combatants: Combatants,
+) {
+    for (shooter, action, pos, attributes, is_player, blinded) in shooters.iter() {
+        let PerformAction::Fire { weapon, ammo, x, y } = *action else {
+            continue;
+        };
+        let Some(equipment) = items
+            .get(weapon)
+            .ok()
+            .and_then(|(item, ..)| item.definition())
+            .and_then(|d| d.equipment)
+        else {
+            continue;
+        };
+        let Some(launcher) = equipment.launcher else {
+            continue;
+        };
+        let Ok((item_data, name, stack, charges)) = items.get_mut(ammo) else {
+            continue;
+        };
+        let name = known.shown_name(item_data, name).to_string();
+        consume(&mut commands, ammo, stack, charges);
+
+        if is_player {
+            log.add(format!("You fire the {}.", name));
+        }
+        let (_, hit) = projectile_impact(&cgd.maps.map[pos.map], &occupants, shooter, pos, (x, y));
+        match hit {
+            Some(target) => {
+                let attack = Attack {
+                    attacker: shooter,
+                    target,
+                    attributes,
+                    skill: 0,
+                    to_hit: equipment.to_hit,
+                    blinded,
+                    damage: &launcher.damage,
+                };
+                resolve_attack(&mut log, &mut commands, &mut loot, &defenders, &mut combatants, attack);
+            }
+            None if is_player => log.add(format!("The {} hits nothing.", name)),
+            None => {}
+        }
+    }
+}
+
+/// Follows the line of fire from `pos` towards the targeted tile. Returns where a projectile lands
+/// and who is standing there, if anyone. The shooter doesn't block their own line of fire.
+fn projectile_impact(
+    map: &Map,
+    occupants: &Occupants,
+    shooter: Entity,
+    pos: &Position,
+    target: (i32, i32),
+) -> ((i32, i32), Option<Entity>) {
+    let occupant_at = |x: i32, y: i32| {
+        occupants
+            .iter()
+            .find(|(e, p)| *e != shooter && p.map == pos.map && p.x == x && p.y == y)
+            .map(|(e, _)| e)
+    };
+    let path = line_of_fire(map, (pos.x, pos.y), target, |x, y| occupant_at(x, y).is_some());
+    let impact = path.last().copied().unwrap_or((pos.x, pos.y));
+    (impact, occupant_at(impact.0, impact.1))
+}
+
+/// Rolls the attack, applies damage and handles the target's death.
+fn resolve_attack(
+    log: &mut GameLog,
+    commands: &mut Commands,
+    loot: &mut EventWriter<LootDropEvent>,
+    defenders: &Defenders,
+    combatants: &mut Combatants,
+    attack: Attack,
+) {
+    let Attack {
+        attacker,
+        target,
+        attributes,
+        ..
+    } = attack;
+    let Ok(
+        [
+            (_, attacker_name, attacker_is_player),
+            (target_stats, target_name, target_is_player),
+        ],
+    ) = combatants.get_many([attacker, target])
+    else {
+        return;
+    };
+    let (target_dv, target_pv) = (target_stats.dv, target_stats.pv);
+    let attacker_name = capitalize(&display_name(attacker_name, attacker_is_player));
+    let target_name = display_name(target_name, target_is_player);
+
+    let Ok((target_attributes, target_skills, target_pos, paralysed)) = defenders.get(target) else {
+        return;
+    };
+    let mode = match (attack.blinded, paralysed) {
+        (true, false) => RollMode::Disadvantage,
+        (false, true) => RollMode::Advantage,
+        _ => RollMode::Normal,
+    };
+    let result = opposed_roll(
+        &attributes.dex,
+        &CheckModifiers::skill(attack.skill + attack.to_hit).with_mode(mode),
+        &target_attributes.dex,
+        &CheckModifiers::skill(target_skills.map_or(0, |s| s.defense) + target_dv),
+    );
+
+    if result.winner == Winner::Defender {
+        log.add(format!(
+            "{} {} {}.",
+            attacker_name,
+            verb("miss", attacker_is_player),
+            target_name
+        ));
+        return;
+    }
+
+    let mut damage = (rng::roll_str(attack.damage) + attributes.str.value() / 20 + attack.skill - target_pv).max(1);
+    if result.attacker == RollResult::Success(RollResultType::Critical) {
+        damage *= 2;
+    }
+    let Ok((mut target_stats, _, _)) = combatants.get_mut(target) else {
+        return;
+    };
+    if target_stats.hp.is_empty() {
+        return;
+    }
+    target_stats.hp.decrease(damage);
+    log.add(format!(
+        "{} {} {} for {} damage.",
+        attacker_name,
+        verb("hit", attacker_is_player),
+        target_name,
+        damage
+    ));
+
+    if target_stats.hp.is_empty() && !target_is_player {
+        let xp = CFG.lock().unwrap().config.experience.xp_for_kill(target_stats.lv);
+        log.add(format!(
+            "{} {} {}!",
+            attacker_name,
+            verb("kill", attacker_is_player),
+            target_name
+        ));
+        commands.entity(target).despawn();
+        loot.write(LootDropEvent { pos: *target_pos });
+        if let Ok((mut attacker_stats, _, _)) = combatants.get_mut(attacker) {
+            attacker_stats.xp += xp;
+        }
+    }
+}
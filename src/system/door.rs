@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+
+use crate::{
+    CurrentGameData, GameState,
+    component::{DetailedName, PerformAction, Player, Position, Stats},
+    config::ItemClass,
+    event::MapChanged,
+    game_event::GameEvent,
+    gamelog::GameLog,
+    item::{CarriedBy, Item, KnownItems},
+    map::{DoorAction, TileType},
+};
+
+const DIRECTIONS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// What the direction asked for is needed for.
+#[derive(Resource, Debug, Clone, Copy)]
+pub enum DirectionPrompt {
+    Door(DoorAction),
+    Bash,
+}
+
+/// Handles the open and close commands. With a single door nearby it's used right away,
+/// otherwise the player is asked for the direction.
+pub fn door_prompt_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    cgd: Res<CurrentGameData>,
+) {
+    let pos = cgd.player_pos;
+    let map = &cgd.maps.map[pos.map];
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        let GameEvent::OperateDoor(action) = *event else {
+            continue;
+        };
+        let doors: Vec<(i32, i32)> = DIRECTIONS
+            .iter()
+            .map(|(dx, dy)| (pos.x + dx, pos.y + dy))
+            .filter(|(x, y)| map.in_bounds(*x, *y) && action.applies_to(map.get_tile_type(*x as usize, *y as usize)))
+            .collect();
+        match doors.as_slice() {
+            [] => log.add(format!("There is no door here to {}.", action.name())),
+            [(x, y)] => events_to_send.push(GameEvent::PlayerOperateDoor { action, x: *x, y: *y }),
+            _ => {
+                log.add(format!("Which door do you want to {}?", action.name()));
+                commands.insert_resource(DirectionPrompt::Door(action));
+                next_state.set(GameState::ChooseDirection);
+            }
+        }
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+}
+
+/// Handles the direction chosen for the door or bash prompt.
+pub fn choose_direction_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    prompt: Option<Res<DirectionPrompt>>,
+    cgd: Res<CurrentGameData>,
+) {
+    let Some(prompt) = prompt else {
+        return;
+    };
+    let pos = cgd.player_pos;
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        if let GameEvent::ChooseDirection { dx, dy } = *event {
+            next_state.set(GameState::InGame);
+            let (x, y) = (pos.x + dx, pos.y + dy);
+            events_to_send.push(match *prompt {
+                DirectionPrompt::Door(action) => GameEvent::PlayerOperateDoor { action, x, y },
+                DirectionPrompt::Bash => GameEvent::PlayerBash { x, y },
+            });
+        }
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+}
+
+pub fn close_direction_prompt(mut commands: Commands) {
+    commands.remove_resource::<DirectionPrompt>();
+}
+
+/// Opens and closes doors. Locked doors can only be opened by someone carrying a key, and doors
+/// can't be closed while something is in the doorway.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn door_system(
+    mut cgd: ResMut<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    known: Res<KnownItems>,
+    users: Query<(Entity, &PerformAction, &Position, Has<Player>)>,
+    keys: Query<(&Item, &DetailedName, &CarriedBy)>,
+    mut changes: EventWriter<MapChanged>,
+    in_the_way: Query<&Position, Or<(With<Stats>, With<Item>)>>,
+) {
+    for (user, action, pos, is_player) in users.iter() {
+        let PerformAction::OperateDoor { action, x, y } = *action else {
+            continue;
+        };
+        let map = &mut cgd.maps.map[pos.map];
+        let message = match (action, map.get_tile_type(x as usize, y as usize)) {
+            (DoorAction::Open, TileType::DoorLocked) => {
+                let key = keys.iter().find(|(item, _, carrier)| {
+                    carrier.0 == user && item.definition().is_some_and(|d| d.class == ItemClass::Key)
+                });
+                match key {
+                    Some((item, name, _)) => {
+                        map.unlock_door(x, y);
+                        map.open_door(x, y);
+                        format!("You unlock the door with the {} and open it.", known.shown_name(item, name))
+                    }
+                    None => {
+                        if is_player {
+                            log.add("This door is locked.");
+                        }
+                        continue;
+                    }
+                }
+            }
+            (DoorAction::Open, _) if map.open_door(x, y) => "You open the door.".to_string(),
+            (DoorAction::Close, _) if in_the_way.iter().any(|p| p.map == pos.map && p.x == x && p.y == y) => {
+                if is_player {
+                    log.add("Something is in the way.");
+                }
+                continue;
+            }
+            (DoorAction::Close, _) if map.close_door(x, y) => "You close the door.".to_string(),
+            _ => continue,
+        };
+        if is_player {
+            log.add(message);
+        }
+        changes.write(MapChanged { map: pos.map });
+    }
+}
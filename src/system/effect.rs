@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+
+use crate::{
+    CFG, CurrentGameData,
+    component::{Player, Position, Stats},
+    effect::{Effect, EffectEvent},
+    event::MapChanged,
+    gamelog::{GameLog, capitalize, display_name, verb},
+    item::{Buc, BucKnown, CarriedBy, Equipped, Item, KnownItems, LootDropEvent},
+    rng,
+    system::terrain::destroyed_message,
+};
+
+/// Carried items with their blessed/cursed state, for identifying and uncursing them.
+type BucItems<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Item,
+        &'static CarriedBy,
+        Option<&'static Buc>,
+        Has<BucKnown>,
+        Has<Equipped>,
+    ),
+>;
+
+/// Applies effects from spells and items to whatever is on the affected tile, or the tile itself.
+/// Killing something with an effect awards experience to the source of the effect, and may make
+/// it drop loot.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn effect_system(
+    mut events: EventReader<EffectEvent>,
+    mut cgd: ResMut<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    mut known: ResMut<KnownItems>,
+    mut commands: Commands,
+    mut loot: EventWriter<LootDropEvent>,
+    mut changes: EventWriter<MapChanged>,
+    mut entities: Query<(Entity, &mut Position, &mut Stats, Option<&Name>, Has<Player>)>,
+    items: BucItems,
+) {
+    for event in events.read() {
+        let pos = event.pos;
+        let occupants: Vec<Entity> = entities
+            .iter()
+            .filter(|(_, p, stats, _, _)| p.map == pos.map && p.x == pos.x && p.y == pos.y && !stats.hp.is_empty())
+            .map(|(e, ..)| e)
+            .collect();
+
+        match &event.effect {
+            Effect::Damage(dice) => {
+                for target in occupants {
+                    let Ok((_, _, mut stats, name, is_player)) = entities.get_mut(target) else {
+                        continue;
+                    };
+                    let damage = rng::roll_str(dice).max(1);
+                    stats.hp.decrease(damage);
+                    let name = display_name(name, is_player);
+                    log.add(format!(
+                        "{} {} {} damage.",
+                        capitalize(&name),
+                        verb("take", is_player),
+                        damage
+                    ));
+                    if !stats.hp.is_empty() || is_player {
+                        continue;
+                    }
+
+                    let xp = CFG.lock().unwrap().config.experience.xp_for_kill(stats.lv);
+                    log.add(format!("{} {}!", capitalize(&name), verb("die", false)));
+                    commands.entity(target).despawn();
+                    loot.write(LootDropEvent { pos });
+                    if let Ok((_, _, mut source_stats, _, _)) = entities.get_mut(event.source) {
+                        source_stats.xp += xp;
+                    }
+                }
+            }
+            Effect::Demolish(dice) => {
+                let map = &mut cgd.maps.map[pos.map];
+                if !map.in_bounds(pos.x, pos.y) {
+                    continue;
+                }
+                let tile = map.get_tile_type(pos.x as usize, pos.y as usize);
+                let name = map.definition(tile).name.clone();
+                if map.damage_tile(pos.x, pos.y, rng::roll_str(dice)) {
+                    if map.is_visible(pos.x as usize, pos.y as usize) {
+                        log.add(destroyed_message(tile, &name));
+                    }
+                    changes.write(MapChanged { map: pos.map });
+                }
+            }
+            Effect::Heal(dice) => {
+                for target in occupants {
+                    let Ok((_, _, mut stats, name, is_player)) = entities.get_mut(target) else {
+                        continue;
+                    };
+                    stats.hp.increase(rng::roll_str(dice));
+                    if is_player {
+                        log.add("You feel better.");
+                    } else {
+                        log.add(format!("{} looks better.", capitalize(&display_name(name, false))));
+                    }
+                }
+            }
+            Effect::Status(kind, duration) => {
+                for target in occupants {
+                    let Ok((_, _, _, name, is_player)) = entities.get(target) else {
+                        continue;
+                    };
+                    kind.apply(&mut commands, target, *duration);
+                    let is = if is_player { "are" } else { "is" };
+                    log.add(format!(
+                        "{} {} {}.",
+                        capitalize(&display_name(name, is_player)),
+                        is,
+                        kind.name()
+                    ));
+                }
+            }
+            Effect::Teleport => {
+                let map = &cgd.maps.map[pos.map];
+                let Ok((_, mut source_pos, _, _, is_player)) = entities.get_mut(event.source) else {
+                    continue;
+                };
+                if !occupants.is_empty() || !map.is_walkable(pos.x, pos.y) {
+                    if is_player {
+                        log.add("You feel a brief tug, but nothing happens.");
+                    }
+                    continue;
+                }
+                *source_pos = pos;
+                if is_player {
+                    log.add("You teleport.");
+                }
+            }
+            Effect::RevealMap => {
+                cgd.maps.map[pos.map].reveal_all();
+                log.add("You sense the layout of your surroundings.");
+            }
+            Effect::Identify => {
+                // Identifies one unknown kind of item, and reveals which carried items are cursed.
+                for target in occupants {
+                    if !entities.get(target).is_ok_and(|(.., is_player)| is_player) {
+                        continue;
+                    }
+                    let carried = || items.iter().filter(move |(_, _, carrier, ..)| carrier.0 == target);
+                    let mut unknown: Vec<&str> = carried()
+                        .filter(|(_, item, ..)| !known.is_known(&item.name))
+                        .map(|(_, item, ..)| item.name.as_str())
+                        .collect();
+                    unknown.sort();
+                    unknown.dedup();
+                    let mut revealed = false;
+                    for (item, ..) in carried().filter(|(_, _, _, _, buc_known, _)| !buc_known) {
+                        commands.entity(item).insert(BucKnown);
+                        revealed = true;
+                    }
+
+                    if !unknown.is_empty() {
+                        let name = unknown[rng::range(0, unknown.len() as i32) as usize];
+                        log.add(format!("The {} is a {}.", known.appearance(name), name));
+                        known.identify(name);
+                    } else if revealed {
+                        log.add("You sense which of your belongings are blessed and which are cursed.");
+                    } else {
+                        log.add("You have nothing to identify.");
+                    }
+                }
+            }
+            Effect::RemoveCurse => {
+                // Uncurses equipped items, and reveals the state of all carried ones.
+                for target in occupants {
+                    if !entities.get(target).is_ok_and(|(.., is_player)| is_player) {
+                        continue;
+                    }
+                    let mut uncursed = false;
+                    for (item, _, carrier, buc, _, equipped) in items.iter() {
+                        if carrier.0 != target {
+                            continue;
+                        }
+                        if equipped && buc == Some(&Buc::Cursed) {
+                            commands.entity(item).insert(Buc::Uncursed);
+                            uncursed = true;
+                        }
+                        commands.entity(item).insert(BucKnown);
+                    }
+                    if uncursed {
+                        log.add("You feel as if someone is helping you.");
+                    } else {
+                        log.add("You feel as if someone is watching over you.");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+
+use crate::{
+    component::{Attributes, DetailedName, PerformAction, Player, Stats},
+    config::{AttributeBonus, EquipSlot, EquipmentDefinition, ItemDefinition},
+    gamelog::GameLog,
+    item::{Buc, BucKnown, CURSE_PENALTY, CarriedBy, CursePenalty, Equipped, Inventory, Item, KnownItems},
+};
+
+/// Equipped items and who they are equipped by.
+pub type EquippedItems<'w, 's> =
+    Query<'w, 's, (Entity, &'static Item, &'static CarriedBy, Option<&'static Buc>), With<Equipped>>;
+
+/// Puts on and takes off equipment. Putting on an item takes off whatever is worn in its slot
+/// already. Cursed equipment can't be taken off, and trying to do so reveals the curse.
+pub fn equip_system(
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    known: Res<KnownItems>,
+    users: Query<(Entity, &PerformAction, Has<Player>)>,
+    items: Query<(&Item, &DetailedName, Option<&Buc>)>,
+    equipped: EquippedItems,
+) {
+    for (user, action, is_player) in users.iter() {
+        let (item, equip) = match *action {
+            PerformAction::Equip { item } => (item, true),
+            PerformAction::Unequip { item } => (item, false),
+            _ => continue,
+        };
+        let Ok((item_data, name, buc)) = items.get(item) else {
+            continue;
+        };
+        let name = known.shown_name(item_data, name);
+        let mut say = |message: String| {
+            if is_player {
+                log.add(message);
+            }
+        };
+
+        if !equip {
+            if buc == Some(&Buc::Cursed) {
+                commands.entity(item).insert(BucKnown);
+                say(format!("You can't take off the {}. It is cursed!", name));
+            } else {
+                commands.entity(item).remove::<Equipped>();
+                say(format!("You take off the {}.", name));
+            }
+            continue;
+        }
+
+        let Some(slot) = item_data.definition().and_then(|d| d.equipment).map(|e| e.slot) else {
+            continue;
+        };
+        let in_slot: Vec<(Entity, &Item, Option<&Buc>)> = equipped
+            .iter()
+            .filter(|(_, other, carrier, _)| carrier.0 == user && slot_of(other) == Some(slot))
+            .map(|(e, other, _, buc)| (e, other, buc))
+            .collect();
+        if in_slot.len() >= slot.capacity() {
+            let (other, other_item, other_buc) = in_slot[0];
+            let other_name = items
+                .get(other)
+                .map_or(other_item.name.as_str(), |(_, n, _)| known.shown_name(other_item, n));
+            if other_buc == Some(&Buc::Cursed) {
+                commands.entity(other).insert(BucKnown);
+                say(format!("You can't take off the {}. It is cursed!", other_name));
+                continue;
+            }
+            commands.entity(other).remove::<Equipped>();
+            say(format!("You take off the {}.", other_name));
+        }
+        commands.entity(item).insert(Equipped);
+        let verb = if slot == EquipSlot::Weapon { "wield" } else { "put on" };
+        say(format!("You {} the {}.", verb, name));
+    }
+}
+
+fn slot_of(item: &Item) -> Option<EquipSlot> {
+    item.definition().and_then(|d| d.equipment).map(|e| e.slot)
+}
+
+/// Recalculates the defense and protection values and attribute bonuses an entity gets from its
+/// equipment, whenever something is put on, taken off or lost.
+pub fn derived_stats_system(
+    mut removed: RemovedComponents<Equipped>,
+    added: Query<(), Added<Equipped>>,
+    changed_inventories: Query<(), Changed<Inventory>>,
+    mut wearers: Query<(&Inventory, &mut Stats, &mut Attributes)>,
+    equipped: Query<&Item, With<Equipped>>,
+) {
+    let removed = removed.read().count() > 0;
+    if !removed && added.is_empty() && changed_inventories.is_empty() {
+        return;
+    }
+    for (inventory, mut stats, mut attributes) in wearers.iter_mut() {
+        let worn: Vec<EquipmentDefinition> = inventory
+            .iter()
+            .filter_map(|item| equipped.get(item).ok())
+            .filter_map(|item| item.definition().and_then(|d| d.equipment))
+            .collect();
+        let (dv, pv, bonus) = equipment_totals(&worn);
+        debug!("Equipment gives DV {}, PV {} and {:?}", dv, pv, bonus);
+        stats.dv = dv;
+        stats.pv = pv;
+        attributes.str.bonus = bonus.str;
+        attributes.dex.bonus = bonus.dex;
+        attributes.con.bonus = bonus.con;
+        attributes.int.bonus = bonus.int;
+    }
+}
+
+/// Sums up the defense value, protection value and attribute bonuses of a set of equipment.
+fn equipment_totals(worn: &[EquipmentDefinition]) -> (i32, i32, AttributeBonus) {
+    let mut bonus = AttributeBonus::default();
+    for equipment in worn {
+        bonus.str += equipment.attributes.str;
+        bonus.dex += equipment.attributes.dex;
+        bonus.con += equipment.attributes.con;
+        bonus.int += equipment.attributes.int;
+    }
+    (worn.iter().map(|e| e.dv).sum(), worn.iter().map(|e| e.pv).sum(), bonus)
+}
+
+/// Keeps the attribute penalties for wearing cursed equipment up to date, as items are equipped,
+/// uncursed or lost.
+pub fn curse_penalty_system(
+    mut commands: Commands,
+    mut wearers: Query<(Entity, &Inventory, &mut Attributes, Option<&mut CursePenalty>)>,
+    items: Query<&Buc, With<Equipped>>,
+) {
+    for (entity, inventory, mut attributes, applied) in wearers.iter_mut() {
+        let cursed = inventory
+            .iter()
+            .filter(|item| items.get(*item).is_ok_and(|buc| *buc == Buc::Cursed))
+            .count() as i32;
+        let penalty = cursed * CURSE_PENALTY;
+        let previous = applied.as_ref().map_or(0, |p| p.0);
+        if penalty == previous {
+            continue;
+        }
+        let change = penalty - previous;
+        attributes.str.modifiers -= change;
+        attributes.dex.modifiers -= change;
+        attributes.con.modifiers -= change;
+        attributes.int.modifiers -= change;
+        match applied {
+            Some(mut applied) => applied.0 = penalty,
+            None => {
+                commands.entity(entity).insert(CursePenalty(penalty));
+            }
+        }
+    }
+}
+
+/// The weapon an entity has equipped and its item definition, if any.
+pub fn wielded_item(wielder: Entity, equipped: &EquippedItems) -> Option<(Entity, ItemDefinition)> {
+    equipped
+        .iter()
+        .filter(|(_, _, carrier, _)| carrier.0 == wielder)
+        .filter_map(|(entity, item, ..)| item.definition().map(|d| (entity, d)))
+        .find(|(_, d)| d.equipment.as_ref().is_some_and(|e| e.slot == EquipSlot::Weapon))
+}
+
+/// The equipment definition of the weapon an entity has equipped, if any.
+pub fn wielded_equipment(wielder: Entity, equipped: &EquippedItems) -> Option<EquipmentDefinition> {
+    wielded_item(wielder, equipped).and_then(|(_, d)| d.equipment)
+}
+
+/// The equipped weapon of an entity, if any: its damage dice and to-hit bonus.
+pub fn wielded_weapon(wielder: Entity, equipped: &EquippedItems) -> Option<(String, i32)> {
+    wielded_equipment(wielder, equipped).map(|e| (e.damage.unwrap_or_else(|| "1d4".to_string()), e.to_hit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn armor(dv: i32, pv: i32, attributes: AttributeBonus) -> EquipmentDefinition {
+        EquipmentDefinition {
+            slot: EquipSlot::Body,
+            damage: None,
+            to_hit: 0,
+            digging: None,
+            dv,
+            pv,
+            attributes,
+            launcher: None,
+        }
+    }
+
+    #[test]
+    fn equipment_totals_add_up() {
+        let worn = vec![
+            armor(
+                1,
+                2,
+                AttributeBonus {
+                    str: 5,
+                    ..Default::default()
+                },
+            ),
+            armor(
+                -1,
+                1,
+                AttributeBonus {
+                    str: 5,
+                    dex: -2,
+                    ..Default::default()
+                },
+            ),
+        ];
+        let (dv, pv, bonus) = equipment_totals(&worn);
+        assert_eq!((dv, pv), (0, 3));
+        assert_eq!(
+            bonus,
+            AttributeBonus {
+                str: 10,
+                dex: -2,
+                con: 0,
+                int: 0
+            }
+        );
+        assert_eq!(equipment_totals(&[]), (0, 0, AttributeBonus::default()));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    CFG, GameEvent, LEVEL_UP_NAME,
+    component::{Attributes, Player, Skills, Stats},
+    gamelog::GameLog,
+    rng,
+    ui::{UIComponents, components::LevelUpScreen},
+};
+use bevy::prelude::*;
+
+/// Levels up entities which have gained enough experience.
+/// Each level raises maximum HP and MP, improves a random attribute and grants skill points.
+#[allow(clippy::type_complexity)]
+pub fn level_up_system(
+    mut log: ResMut<GameLog>,
+    mut query: Query<(&mut Stats, &mut Attributes, Option<&mut Skills>, Has<Player>), Changed<Stats>>,
+) {
+    let cfg = CFG.lock().unwrap();
+    let experience = &cfg.config.experience;
+
+    for (mut stats, mut attributes, mut skills, player) in query.iter_mut() {
+        while stats.lv < experience.max_level && stats.xp >= experience.xp_for_level(stats.lv + 1) {
+            stats.lv += 1;
+
+            let hp_gain = rng::roll_str(&experience.hp_per_level).max(1);
+            stats.hp.max += hp_gain;
+            stats.hp.increase(hp_gain);
+            let mp_gain = rng::roll_str(&experience.mp_per_level).max(0);
+            stats.mp.max += mp_gain;
+            stats.mp.increase(mp_gain);
+
+            let (name, attribute) = match rng::roll_dice(1, 4) {
+                1 => ("stronger", &mut attributes.str),
+                2 => ("more agile", &mut attributes.dex),
+                3 => ("tougher", &mut attributes.con),
+                _ => ("smarter", &mut attributes.int),
+            };
+            attribute.base += rng::roll_str(&experience.attribute_gain).max(1);
+
+            if let Some(skills) = skills.as_mut() {
+                skills.unspent += experience.skill_points_per_level;
+            }
+
+            if player {
+                log.add(format!("Welcome to level {}! You feel {}.", stats.lv, name));
+                if skills.is_some() {
+                    log.add("You have new skill points to spend.");
+                }
+            }
+        }
+    }
+}
+
+/// Spends skill points chosen on the level up screen.
+pub fn skill_allocation_system(
+    mut event_queue: EventReader<GameEvent>,
+    mut log: ResMut<GameLog>,
+    mut query: Query<&mut Skills, With<Player>>,
+) {
+    for event in event_queue.read() {
+        if let GameEvent::AllocateSkillPoint(skill) = event
+            && let Ok(mut skills) = query.single_mut()
+            && skills.allocate(*skill)
+        {
+            log.add(format!(
+                "Your {} skill increases to {}.",
+                format!("{:?}", skill).to_lowercase(),
+                skills.get(*skill)
+            ));
+        }
+    }
+}
+
+/// Keeps the level up screen in sync with the player's stats and skills.
+pub fn level_up_screen_system(mut uicomps: ResMut<UIComponents>, query: Query<(&Stats, &Skills), With<Player>>) {
+    let Ok((stats, skills)) = query.single() else {
+        return;
+    };
+    let next_level_xp = CFG.lock().unwrap().config.experience.xp_for_level(stats.lv + 1);
+    if let Some(screen) = uicomps
+        .comps
+        .get_mut(LEVEL_UP_NAME)
+        .and_then(|c| c.component.downcast_mut::<LevelUpScreen>())
+    {
+        screen.set_player(stats.clone(), skills.clone(), next_level_xp);
+    }
+}
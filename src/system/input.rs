@@ -0,0 +1,414 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_ratatui::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::{
+    CurrentGameData, GameState, KEY_HINTS_NAME, MenuState, TurnState, UIConfig,
+    component::{Activity, Enemy, Intent, Position},
+    config::{InputContext, key_event_to_string, key_sequence_to_string},
+    game_event::GameEvent,
+    gamelog::GameLog,
+    ui::{UIComponents, components::KeyHints},
+};
+
+type KeyMap = HashMap<Vec<crossterm::event::KeyEvent>, GameEvent>;
+
+/// The largest count which can be typed in front of a command.
+const MAX_COUNT: u32 = 999;
+
+/// What the keys typed so far amount to.
+#[derive(Debug, PartialEq)]
+pub enum KeyLookup {
+    /// A complete key sequence, to be sent the given number of times.
+    Event(GameEvent, u32),
+    /// The start of a count or of a longer key sequence.
+    Pending,
+    /// The keys aren't bound to anything.
+    Unbound,
+}
+
+/// The keys typed so far towards a key sequence, and the count typed in front of it.
+#[derive(Resource, Default, Debug)]
+pub struct KeySequence {
+    keys: Vec<crossterm::event::KeyEvent>,
+    count: Option<u32>,
+    last_key: Option<Instant>,
+}
+
+impl KeySequence {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.count.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.count = None;
+        self.last_key = None;
+    }
+
+    /// Adds a key and looks up what the keys typed so far are bound to. Digits which aren't bound
+    /// themselves start a count when `counts` is set. A sequence which is bound, but also the start
+    /// of a longer one, waits for the next key or the timeout.
+    pub fn push(&mut self, key: crossterm::event::KeyEvent, keymap: &KeyMap, counts: bool, now: Instant) -> KeyLookup {
+        self.last_key = Some(now);
+        if key.code == KeyCode::Esc && !self.is_empty() {
+            self.clear();
+            return KeyLookup::Pending;
+        }
+        if counts
+            && self.keys.is_empty()
+            && !keymap.contains_key(&vec![key])
+            && let Some(digit) = count_digit(&key, self.count.is_some())
+        {
+            self.count = Some((self.count.unwrap_or(0) * 10 + digit).min(MAX_COUNT));
+            return KeyLookup::Pending;
+        }
+
+        self.keys.push(key);
+        if self.continues(keymap) {
+            return KeyLookup::Pending;
+        }
+        let count = self.count.unwrap_or(1);
+        let lookup = match keymap.get(&self.keys) {
+            Some(event) => KeyLookup::Event(event.clone(), count),
+            None => KeyLookup::Unbound,
+        };
+        let retry = lookup == KeyLookup::Unbound && self.keys.len() > 1;
+        self.clear();
+        // A key which doesn't continue the sequence starts a new one, so it isn't swallowed
+        if retry { self.push(key, keymap, counts, now) } else { lookup }
+    }
+
+    /// Ends a sequence nothing was typed for since the timeout, sending what it's bound to.
+    pub fn expire(&mut self, keymap: &KeyMap, timeout: Duration, now: Instant) -> Option<(GameEvent, u32)> {
+        let last_key = self.last_key?;
+        if self.is_empty() || now.duration_since(last_key) < timeout {
+            return None;
+        }
+        let event = keymap.get(&self.keys).map(|event| (event.clone(), self.count.unwrap_or(1)));
+        self.clear();
+        event
+    }
+
+    /// The key sequences starting with the keys typed so far, and what they're bound to.
+    pub fn continuations(&self, keymap: &KeyMap) -> Vec<(String, String)> {
+        if self.keys.is_empty() {
+            return Vec::new();
+        }
+        let mut continuations: Vec<(String, String)> = keymap
+            .iter()
+            .filter(|(keys, _)| keys.len() > self.keys.len() && keys.starts_with(&self.keys))
+            .map(|(keys, event)| (key_sequence_to_string(&keys[self.keys.len()..]), event.to_string()))
+            .collect();
+        continuations.sort();
+        continuations
+    }
+
+    /// The count and keys typed so far, e.g. `5<shift-z>`.
+    pub fn prefix(&self) -> String {
+        let count = self.count.map(|c| c.to_string()).unwrap_or_default();
+        count + &key_sequence_to_string(&self.keys)
+    }
+
+    fn continues(&self, keymap: &KeyMap) -> bool {
+        keymap
+            .keys()
+            .any(|keys| keys.len() > self.keys.len() && keys.starts_with(&self.keys))
+    }
+}
+
+fn count_digit(key: &crossterm::event::KeyEvent, started: bool) -> Option<u32> {
+    if !(key.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char('0') if !started => None,
+        KeyCode::Char(c) => c.to_digit(10),
+        _ => None,
+    }
+}
+
+/// While this exists, the next key pressed is sent as it is instead of looking up its binding.
+#[derive(Resource, Debug)]
+pub struct KeyCapture;
+
+/// A game event which is sent again on each of the player's turns, after a count was typed in
+/// front of its key.
+#[derive(Resource, Debug)]
+pub struct RepeatedEvent {
+    pub event: GameEvent,
+    pub remaining: u32,
+}
+
+/// The input contexts keys are looked up in, from the top down: the visible UI components from
+/// the topmost layer down, then the sub-states, the game state and finally the global bindings.
+pub fn input_contexts(
+    state: &GameState,
+    menu_state: Option<&MenuState>,
+    turn_state: Option<&TurnState>,
+    uicomps: &UIComponents,
+) -> Vec<InputContext> {
+    let mut components: Vec<_> = uicomps.comps.iter().filter(|(_, c)| c.visible).collect();
+    components.sort_by_key(|(_, c)| std::cmp::Reverse(c.layer));
+    let mut contexts: Vec<InputContext> = components
+        .into_iter()
+        .map(|(name, _)| InputContext::Component(name.clone()))
+        .collect();
+    contexts.extend(menu_state.map(|s| InputContext::Menu(*s)));
+    contexts.extend(turn_state.map(|s| InputContext::Turn(*s)));
+    contexts.push(InputContext::State(*state));
+    contexts.push(InputContext::Global);
+    contexts
+}
+
+/// Turns key presses into game events, using the key bindings of the current input contexts.
+#[allow(clippy::too_many_arguments)]
+pub fn keyboard_input_system(
+    mut events: EventReader<KeyEvent>,
+    mut game_events: EventWriter<GameEvent>,
+    mut sequence: ResMut<KeySequence>,
+    uiconfig: Res<UIConfig>,
+    state: Res<State<GameState>>,
+    menu_state: Option<Res<State<MenuState>>>,
+    turn_state: Option<Res<State<TurnState>>>,
+    uicomps: Res<UIComponents>,
+    capture: Option<Res<KeyCapture>>,
+    mut commands: Commands,
+) {
+    if capture.is_some() {
+        // Keys after the captured one are left for the next frame
+        if let Some(event) = events.read().next() {
+            commands.remove_resource::<KeyCapture>();
+            let key = crossterm::event::KeyEvent::new(event.code, event.modifiers);
+            game_events.write(GameEvent::CapturedKey(key_event_to_string(&key)));
+        }
+        return;
+    }
+    if events.is_empty() && sequence.is_empty() {
+        return;
+    }
+    let contexts = input_contexts(
+        &state,
+        menu_state.as_ref().map(|s| s.get()),
+        turn_state.as_ref().map(|s| s.get()),
+        &uicomps,
+    );
+    let keymap = &uiconfig.keymap(&contexts);
+    let counts = *state.get() == GameState::InGame;
+    let now = Instant::now();
+    let mut found = Vec::new();
+    for event in events.read() {
+        debug!("KeyEvent received: {event:?}");
+        // Any key stops a repeated command
+        commands.remove_resource::<RepeatedEvent>();
+        let key = crossterm::event::KeyEvent::new(event.code, event.modifiers);
+        if let KeyLookup::Event(ge, count) = sequence.push(key, keymap, counts, now) {
+            found.push((ge, count));
+        }
+    }
+    if !sequence.is_empty() {
+        let timeout = Duration::from_millis(uiconfig.key_sequence_timeout);
+        // Only mutate the sequence when it does expire, the key hints are updated on changes
+        if let Some(last_key) = sequence.last_key
+            && now.duration_since(last_key) >= timeout
+        {
+            found.extend(sequence.expire(keymap, timeout, now));
+        }
+    }
+
+    for (ge, count) in found {
+        debug!("Key pressed for game event: {ge:?} (x{count})");
+        if count > 1 {
+            commands.insert_resource(RepeatedEvent {
+                event: ge,
+                remaining: count,
+            });
+        } else {
+            game_events.write(ge);
+        }
+    }
+}
+
+/// Sends a repeated game event once per turn while the player is waiting for input. Stops as soon
+/// as an enemy comes into view, like activities do.
+pub fn repeat_event_system(
+    cgd: Res<CurrentGameData>,
+    repeat: Option<ResMut<RepeatedEvent>>,
+    mut game_events: EventWriter<GameEvent>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    player_query: Query<(&Position, Has<Intent>, Has<Activity>)>,
+    enemy_query: Query<(&Position, &Name), With<Enemy>>,
+) {
+    let Some(mut repeat) = repeat else {
+        return;
+    };
+    let Some(Ok((pos, has_intent, has_activity))) = cgd.player.map(|p| player_query.get(p)) else {
+        return;
+    };
+    if has_intent || has_activity {
+        return;
+    }
+    let map = &cgd.maps.map[pos.map];
+    if let Some((_, name)) = enemy_query
+        .iter()
+        .find(|(p, _)| p.map == pos.map && map.is_visible(p.x as usize, p.y as usize))
+    {
+        log.add(format!("You stop - {name} comes into view."));
+        commands.remove_resource::<RepeatedEvent>();
+        return;
+    }
+
+    game_events.write(repeat.event.clone());
+    repeat.remaining -= 1;
+    if repeat.remaining == 0 {
+        commands.remove_resource::<RepeatedEvent>();
+    }
+}
+
+/// Counts and repeats only apply to the game they were typed in.
+pub fn stop_repeating_system(mut sequence: ResMut<KeySequence>, mut commands: Commands) {
+    sequence.clear();
+    commands.remove_resource::<RepeatedEvent>();
+}
+
+/// Shows which keys can follow the ones typed so far.
+pub fn key_hints_system(
+    sequence: Res<KeySequence>,
+    uiconfig: Res<UIConfig>,
+    state: Res<State<GameState>>,
+    menu_state: Option<Res<State<MenuState>>>,
+    turn_state: Option<Res<State<TurnState>>>,
+    mut uicomps: ResMut<UIComponents>,
+) {
+    let contexts = input_contexts(
+        &state,
+        menu_state.as_ref().map(|s| s.get()),
+        turn_state.as_ref().map(|s| s.get()),
+        &uicomps,
+    );
+    let hints = sequence.continuations(&uiconfig.keymap(&contexts));
+    let show = !sequence.is_empty();
+    let Some(c) = uicomps.comps.get_mut(KEY_HINTS_NAME) else {
+        return;
+    };
+    if !show && !c.visible {
+        return;
+    }
+    c.visible = show;
+    if let Some(key_hints) = c.component.downcast_mut::<KeyHints>() {
+        key_hints.set_hints(sequence.prefix(), hints);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{InputContextConfig, parse_key_sequence};
+
+    fn keymap(bindings: &[(&str, GameEvent)]) -> KeyMap {
+        bindings
+            .iter()
+            .map(|(keys, event)| (parse_key_sequence(keys).unwrap(), event.clone()))
+            .collect()
+    }
+
+    fn key(raw: &str) -> crossterm::event::KeyEvent {
+        parse_key_sequence(raw).unwrap()[0]
+    }
+
+    #[test]
+    fn sequences_wait_for_their_last_key() {
+        let keymap = keymap(&[("<q>", GameEvent::Quit), ("<shift-z><shift-q>", GameEvent::ShowMainMenu)]);
+        let now = Instant::now();
+        let mut sequence = KeySequence::default();
+        assert_eq!(sequence.push(key("<shift-z>"), &keymap, true, now), KeyLookup::Pending);
+        assert_eq!(
+            sequence.continuations(&keymap),
+            vec![("<shift-q>".to_string(), "ShowMainMenu".to_string())]
+        );
+        assert_eq!(
+            sequence.push(key("<shift-q>"), &keymap, true, now),
+            KeyLookup::Event(GameEvent::ShowMainMenu, 1)
+        );
+        // A key which doesn't continue the sequence is looked up on its own
+        sequence.push(key("<shift-z>"), &keymap, true, now);
+        assert_eq!(
+            sequence.push(key("<q>"), &keymap, true, now),
+            KeyLookup::Event(GameEvent::Quit, 1)
+        );
+        assert!(sequence.is_empty());
+    }
+
+    #[test]
+    fn counts_are_typed_before_the_key() {
+        let keymap = keymap(&[("<j>", GameEvent::PlayerWait), ("<0>", GameEvent::Quit)]);
+        let now = Instant::now();
+        let mut sequence = KeySequence::default();
+        assert_eq!(sequence.push(key("<1>"), &keymap, true, now), KeyLookup::Pending);
+        assert_eq!(sequence.push(key("<2>"), &keymap, true, now), KeyLookup::Pending);
+        assert_eq!(sequence.prefix(), "12");
+        assert_eq!(
+            sequence.push(key("<j>"), &keymap, true, now),
+            KeyLookup::Event(GameEvent::PlayerWait, 12)
+        );
+        // Bound digits aren't counts, and counts can be turned off
+        assert_eq!(
+            sequence.push(key("<0>"), &keymap, true, now),
+            KeyLookup::Event(GameEvent::Quit, 1)
+        );
+        assert_eq!(sequence.push(key("<5>"), &keymap, false, now), KeyLookup::Unbound);
+    }
+
+    #[test]
+    fn bound_prefixes_are_sent_after_the_timeout() {
+        let keymap = keymap(&[("<g>", GameEvent::PlayerPickUp), ("<g><g>", GameEvent::PlayerWait)]);
+        let now = Instant::now();
+        let timeout = Duration::from_millis(500);
+        let mut sequence = KeySequence::default();
+        assert_eq!(sequence.push(key("<g>"), &keymap, true, now), KeyLookup::Pending);
+        assert_eq!(sequence.expire(&keymap, timeout, now), None);
+        assert_eq!(
+            sequence.expire(&keymap, timeout, now + timeout),
+            Some((GameEvent::PlayerPickUp, 1))
+        );
+        assert!(sequence.is_empty());
+    }
+
+    #[test]
+    fn contexts_fall_through_unless_configured_not_to() {
+        let mut uiconfig = UIConfig::default();
+        let context = |name: &str| InputContext::try_from(name.to_string()).unwrap();
+        assert_eq!(context("InGame"), InputContext::State(GameState::InGame));
+        assert_eq!(context("PlayersTurn"), InputContext::Turn(TurnState::PlayersTurn));
+        assert_eq!(context("level_up"), InputContext::Component("level_up".to_string()));
+        assert!(InputContext::try_from("NoSuchState".to_string()).is_err());
+
+        uiconfig
+            .keybindings
+            .insert(context("Global"), keymap(&[("<q>", GameEvent::Quit)]));
+        uiconfig.keybindings.insert(
+            context("InGame"),
+            keymap(&[("<j>", GameEvent::PlayerWait), ("<i>", GameEvent::ShowInventory)]),
+        );
+        uiconfig
+            .keybindings
+            .insert(context("level_up"), keymap(&[("<j>", GameEvent::NextMenuItem)]));
+        let stack = [context("level_up"), context("InGame"), context("Global")];
+        let keys = uiconfig.keymap(&stack);
+        assert_eq!(keys.get(&vec![key("<j>")]), Some(&GameEvent::NextMenuItem));
+        assert_eq!(keys.get(&vec![key("<i>")]), Some(&GameEvent::ShowInventory));
+        assert_eq!(keys.get(&vec![key("<q>")]), Some(&GameEvent::Quit));
+
+        uiconfig
+            .input_contexts
+            .insert(context("level_up"), InputContextConfig { fallthrough: false });
+        let keys = uiconfig.keymap(&stack);
+        assert_eq!(keys.len(), 1);
+    }
+}
@@ -1,14 +1,18 @@
 use crate::{
     CurrentGameData, PerformAction, SpendEnergy,
-    component::{Energy, Intent, Player, Position, Speed},
+    component::{Energy, Intent, Player, Position, Speed, Stats},
     event::{PlayerMoveRelativeEvent, PlayerSpentEnergy},
+    gamelog::GameLog,
+    item::Item,
+    map::DoorAction,
     rng,
 };
 use bevy::prelude::*;
 
+#[allow(clippy::type_complexity)]
 pub fn produce_intents_system(
     cgd: Res<CurrentGameData>,
-    query: Query<(Entity, &Position), Without<Player>>,
+    query: Query<(Entity, &Position), (With<Energy>, Without<Player>)>,
     mut commands: Commands,
 ) {
     // Here we decide what an entity wants to do next.
@@ -30,10 +34,18 @@ pub fn produce_intents_system(
     }
 }
 
-#[allow(clippy::collapsible_if, clippy::single_match)]
+#[allow(
+    clippy::collapsible_if,
+    clippy::single_match,
+    clippy::type_complexity,
+    clippy::too_many_arguments
+)]
 pub fn process_intents_system(
     cgd: Res<CurrentGameData>,
     query: Query<(Entity, &Intent, &Energy, &Speed, Option<&Player>)>,
+    occupants: Query<(Entity, &Position), (With<Stats>, Without<Player>)>,
+    floor_items: Query<&Position, With<Item>>,
+    mut log: ResMut<GameLog>,
     mut move_queue: EventWriter<PlayerMoveRelativeEvent>,
     mut energy_queue: EventWriter<PlayerSpentEnergy>,
     mut commands: Commands,
@@ -46,7 +58,25 @@ pub fn process_intents_system(
         match *intent {
             Intent::MoveRelative { dx, dy } => {
                 if let Some(_player) = player {
-                    if cgd.maps.map[cgd.player_pos.map].is_walkable(cgd.player_pos.x + dx, cgd.player_pos.y + dy) {
+                    let (x, y) = (cgd.player_pos.x + dx, cgd.player_pos.y + dy);
+                    if let Some((target, _)) = occupants
+                        .iter()
+                        .find(|(_, pos)| pos.map == cgd.player_pos.map && pos.x == x && pos.y == y)
+                    {
+                        debug!("Entity is player, attacking {target:?}");
+                        commands.entity(entity).insert(PerformAction::MeleeAttack { target });
+                        energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                    } else if DoorAction::Open
+                        .applies_to(cgd.maps.map[cgd.player_pos.map].get_tile_type(x as usize, y as usize))
+                    {
+                        debug!("Entity is player, opening the door at {x},{y}");
+                        commands.entity(entity).insert(PerformAction::OperateDoor {
+                            action: DoorAction::Open,
+                            x,
+                            y,
+                        });
+                        energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                    } else if cgd.maps.map[cgd.player_pos.map].is_walkable(x, y) {
                         debug!("Entity is player, sending PlayerMoveRelativeEvent");
                         move_queue.write(PlayerMoveRelativeEvent { dx, dy });
                         energy_queue.write(PlayerSpentEnergy(base_energy_cost));
@@ -66,6 +96,84 @@ pub fn process_intents_system(
                     }
                 }
             }
+            Intent::Nothing => {
+                if player.is_some() {
+                    debug!("Entity is player, waiting a turn");
+                    energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                } else {
+                    let needs = (base_energy_cost as f32 * speed.speed) as i32;
+                    if energy.energy >= needs {
+                        commands.entity(entity).insert(SpendEnergy(needs));
+                    }
+                }
+            }
+            Intent::Throw { item, x, y } if player.is_some() => {
+                debug!("Entity is player, throwing {item:?} at {x},{y}");
+                commands.entity(entity).insert(PerformAction::Throw { item, x, y });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::Fire { weapon, ammo, x, y } if player.is_some() => {
+                debug!("Entity is player, firing {ammo:?} from {weapon:?} at {x},{y}");
+                commands.entity(entity).insert(PerformAction::Fire { weapon, ammo, x, y });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::UseItem { item, x, y } if player.is_some() => {
+                debug!("Entity is player, using {item:?} at {x},{y}");
+                commands.entity(entity).insert(PerformAction::UseItem { item, x, y });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::Equip { item } if player.is_some() => {
+                debug!("Entity is player, equipping {item:?}");
+                commands.entity(entity).insert(PerformAction::Equip { item });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::Unequip { item } if player.is_some() => {
+                debug!("Entity is player, unequipping {item:?}");
+                commands.entity(entity).insert(PerformAction::Unequip { item });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::Drop { item } if player.is_some() => {
+                debug!("Entity is player, dropping {item:?}");
+                commands.entity(entity).insert(PerformAction::Drop { item });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
+            Intent::PickUp if player.is_some() => {
+                let here = cgd.player_pos;
+                if floor_items
+                    .iter()
+                    .any(|p| p.map == here.map && p.x == here.x && p.y == here.y)
+                {
+                    debug!("Entity is player, picking up items");
+                    commands.entity(entity).insert(PerformAction::PickUp);
+                    energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                } else {
+                    log.add("There is nothing here to pick up.");
+                }
+            }
+            Intent::OperateDoor { action, x, y } if player.is_some() => {
+                if action.applies_to(cgd.maps.map[cgd.player_pos.map].get_tile_type(x as usize, y as usize)) {
+                    debug!("Entity is player, trying to {} the door at {x},{y}", action.name());
+                    commands.entity(entity).insert(PerformAction::OperateDoor { action, x, y });
+                    energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                } else {
+                    log.add(format!("There is no door there to {}.", action.name()));
+                }
+            }
+            Intent::Bash { x, y } if player.is_some() => {
+                let map = &cgd.maps.map[cgd.player_pos.map];
+                if map.in_bounds(x, y) && !map.is_walkable(x, y) {
+                    debug!("Entity is player, bashing the tile at {x},{y}");
+                    commands.entity(entity).insert(PerformAction::Bash { x, y });
+                    energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+                } else {
+                    log.add("There is nothing there to bash.");
+                }
+            }
+            Intent::Cast { spell, x, y } if player.is_some() => {
+                debug!("Entity is player, casting spell {spell} at {x},{y}");
+                commands.entity(entity).insert(PerformAction::Cast { spell, x, y });
+                energy_queue.write(PlayerSpentEnergy(base_energy_cost));
+            }
             _ => {}
         }
     }
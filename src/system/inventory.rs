@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+
+use crate::{
+    EQUIPMENT_NAME, GameState, INVENTORY_NAME, ITEM_DETAIL_NAME, UIComponent, UIComponentData, UIComponents,
+    component::Player,
+    config::{EquipSlot, ItemClass},
+    game_event::GameEvent,
+    item::{ItemAction, KnownItems, item_details},
+    system::item::CarriedItems,
+    ui::components::{ItemDetail, ItemList},
+};
+
+/// Equipment slots in the order they are listed on the equipment screen.
+const SLOTS: [EquipSlot; 9] = [
+    EquipSlot::Weapon,
+    EquipSlot::Head,
+    EquipSlot::Body,
+    EquipSlot::Hands,
+    EquipSlot::Legs,
+    EquipSlot::Feet,
+    EquipSlot::Ring,
+    EquipSlot::Ring,
+    EquipSlot::Amulet,
+];
+
+/// Order in which item classes are listed in the inventory.
+const CLASS_ORDER: [ItemClass; 10] = [
+    ItemClass::Weapon,
+    ItemClass::Ammo,
+    ItemClass::Armor,
+    ItemClass::Ring,
+    ItemClass::Amulet,
+    ItemClass::Potion,
+    ItemClass::Scroll,
+    ItemClass::Wand,
+    ItemClass::Throwable,
+    ItemClass::Key,
+];
+
+/// The item shown in the item details, and the screen to go back to afterwards.
+#[derive(Resource, Debug)]
+pub struct ItemDetailTarget {
+    pub item: Entity,
+    pub back: GameState,
+}
+
+fn insert_screen(uicomps: &mut UIComponents, name: &str, component: Box<dyn UIComponent>, layer: i32) {
+    uicomps.comps.insert(
+        name.to_string(),
+        UIComponentData {
+            component,
+            visible: true,
+            layer,
+        },
+    );
+}
+
+fn hide_screen(uicomps: &mut UIComponents, name: &str) {
+    if let Some(c) = uicomps.comps.get_mut(name) {
+        c.visible = false;
+    }
+}
+
+/// Lists everything the player carries, grouped by class.
+pub fn show_inventory(
+    mut uicomps: ResMut<UIComponents>,
+    known: Res<KnownItems>,
+    player: Query<Entity, With<Player>>,
+    items: CarriedItems,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let mut carried: Vec<(usize, String, Entity)> = items
+        .iter()
+        .filter(|(.., carrier)| carrier.0 == player)
+        .map(|(e, item, name, stack, charges, buc, buc_known, equipped, _)| {
+            let class = item
+                .definition()
+                .and_then(|d| CLASS_ORDER.iter().position(|c| *c == d.class))
+                .unwrap_or(CLASS_ORDER.len());
+            let mut shown = known.display_name(item, name, stack, charges, buc.filter(|_| buc_known));
+            if equipped {
+                shown.push_str(" (equipped)");
+            }
+            (class, shown, e)
+        })
+        .collect();
+    carried.sort();
+
+    let mut list = ItemList::new("Inventory");
+    for (_, text, item) in carried {
+        list.add_entry(text, Some(item));
+    }
+    insert_screen(&mut uicomps, INVENTORY_NAME, Box::new(list), 1);
+}
+
+pub fn hide_inventory(mut uicomps: ResMut<UIComponents>) {
+    hide_screen(&mut uicomps, INVENTORY_NAME);
+}
+
+/// Lists the player's equipment slots and what is worn in them.
+pub fn show_equipment(
+    mut uicomps: ResMut<UIComponents>,
+    known: Res<KnownItems>,
+    player: Query<Entity, With<Player>>,
+    items: CarriedItems,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let mut worn: Vec<(EquipSlot, String, Entity)> = items
+        .iter()
+        .filter(|(.., equipped, carrier)| *equipped && carrier.0 == player)
+        .filter_map(|(e, item, name, stack, charges, buc, buc_known, ..)| {
+            let slot = item.definition()?.equipment?.slot;
+            let shown = known.display_name(item, name, stack, charges, buc.filter(|_| buc_known));
+            Some((slot, shown, e))
+        })
+        .collect();
+
+    let mut list = ItemList::new("Equipment");
+    for slot in SLOTS {
+        let entry = worn.iter().position(|(s, ..)| *s == slot).map(|i| worn.remove(i));
+        match entry {
+            Some((_, text, item)) => list.add_entry(format!("{:<8} {}", format!("{:?}:", slot), text), Some(item)),
+            None => list.add_entry(format!("{:<8} -", format!("{:?}:", slot)), None),
+        };
+    }
+    insert_screen(&mut uicomps, EQUIPMENT_NAME, Box::new(list), 1);
+}
+
+pub fn hide_equipment(mut uicomps: ResMut<UIComponents>) {
+    hide_screen(&mut uicomps, EQUIPMENT_NAME);
+}
+
+/// Opens the item details for the item picked on the inventory or equipment screen.
+pub fn open_item_detail_system(
+    mut events: EventReader<GameEvent>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        if let GameEvent::ShowItemDetail(item) = event {
+            commands.insert_resource(ItemDetailTarget {
+                item: *item,
+                back: *state.get(),
+            });
+            next_state.set(GameState::ItemDetail);
+        }
+    }
+}
+
+/// Goes back from the item details to the screen they were opened from.
+pub fn close_item_detail_system(
+    mut events: EventReader<GameEvent>,
+    target: Res<ItemDetailTarget>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        if let GameEvent::CloseItemDetail = event {
+            next_state.set(target.back);
+        }
+    }
+}
+
+pub fn show_item_detail(
+    mut uicomps: ResMut<UIComponents>,
+    known: Res<KnownItems>,
+    target: Res<ItemDetailTarget>,
+    items: CarriedItems,
+) {
+    let Ok((item, item_data, name, stack, charges, buc, buc_known, equipped, _)) = items.get(target.item) else {
+        return;
+    };
+    let Some(definition) = item_data.definition() else {
+        return;
+    };
+    let buc = buc.filter(|_| buc_known);
+    let title = known.display_name(item_data, name, stack, charges, buc);
+    let lines = item_details(&definition, known.is_known(&item_data.name), buc.copied());
+    let actions = [
+        ItemAction::Use,
+        ItemAction::Throw,
+        ItemAction::Equip,
+        ItemAction::Remove,
+        ItemAction::Drop,
+    ]
+    .into_iter()
+    .filter(|action| action.allows(&definition, equipped))
+    .collect();
+    insert_screen(
+        &mut uicomps,
+        ITEM_DETAIL_NAME,
+        Box::new(ItemDetail::new(item, title, lines, actions)),
+        2,
+    );
+}
+
+pub fn hide_item_detail(mut uicomps: ResMut<UIComponents>, mut commands: Commands) {
+    hide_screen(&mut uicomps, ITEM_DETAIL_NAME);
+    commands.remove_resource::<ItemDetailTarget>();
+}
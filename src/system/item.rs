@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+
+use crate::{
+    CFG, CurrentGameData, GameState, ITEM_MENU_NAME, UIComponent, UIComponentData, UIComponents,
+    component::{DetailedName, PerformAction, Player, Position, Render, Stats},
+    effect::EffectEvent,
+    game_event::GameEvent,
+    gamelog::GameLog,
+    item::{
+        Buc, BucKnown, CarriedBy, Charges, Equipped, Item, ItemAction, KnownItems, LootDropEvent, Stack, consume,
+        item_display_name, item_render, new_item,
+    },
+    rng,
+    targeting::TargetingPurpose,
+    ui::components::Menu,
+};
+
+/// Items with everything needed to list them, and who carries them.
+pub type CarriedItems<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Item,
+        &'static DetailedName,
+        Option<&'static Stack>,
+        Option<&'static Charges>,
+        Option<&'static Buc>,
+        Has<BucKnown>,
+        Has<Equipped>,
+        &'static CarriedBy,
+    ),
+>;
+
+/// What the item menu currently being shown is for.
+#[derive(Resource, Debug)]
+pub struct ItemMenuAction(pub ItemAction);
+
+/// Opens the item menu for the requested action.
+pub fn open_item_menu_system(
+    mut events: EventReader<GameEvent>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        if let GameEvent::ShowItemMenu(action) = event {
+            commands.insert_resource(ItemMenuAction(*action));
+            next_state.set(GameState::ItemMenu);
+        }
+    }
+}
+
+/// Builds the item menu from the carried items which can be used for the action, and shows it.
+pub fn show_item_menu(
+    mut uicomps: ResMut<UIComponents>,
+    action: Res<ItemMenuAction>,
+    known: Res<KnownItems>,
+    player: Query<Entity, With<Player>>,
+    items: CarriedItems,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let mut menu = Menu::new();
+    menu.set_title(action.0.title());
+    let mut carried: Vec<(Entity, String)> = items
+        .iter()
+        .filter(|(.., carrier)| carrier.0 == player)
+        .filter(|(_, item, .., equipped, _)| item.definition().is_some_and(|d| action.0.allows(&d, *equipped)))
+        .map(|(e, item, name, stack, charges, buc, buc_known, equipped, _)| {
+            let mut shown = known.display_name(item, name, stack, charges, buc.filter(|_| buc_known));
+            if equipped {
+                shown.push_str(" (equipped)");
+            }
+            (e, shown)
+        })
+        .collect();
+    carried.sort_by(|a, b| a.1.cmp(&b.1));
+    for (entity, name) in carried.iter() {
+        menu.add_item((
+            name.clone(),
+            Some(GameEvent::ChooseItemAction {
+                item: *entity,
+                action: action.0,
+            }),
+        ));
+    }
+    if carried.is_empty() {
+        menu.add_item(("You don't have anything suitable.", Some(GameEvent::ReturnToGame)));
+    }
+
+    uicomps.comps.insert(
+        ITEM_MENU_NAME.to_string(),
+        UIComponentData {
+            component: Box::new(menu) as Box<dyn UIComponent>,
+            visible: true,
+            layer: 1,
+        },
+    );
+}
+
+pub fn hide_item_menu(mut uicomps: ResMut<UIComponents>, mut commands: Commands) {
+    if let Some(c) = uicomps.comps.get_mut(ITEM_MENU_NAME) {
+        c.visible = false;
+    }
+    commands.remove_resource::<ItemMenuAction>();
+}
+
+/// Handles the choice of what to do with an item, made in the item menu or the item details:
+/// thrown items and items used on a target go through targeting mode first, the others are used
+/// right away.
+pub fn item_action_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    player: Query<&Position, With<Player>>,
+    items: Query<&Item>,
+) {
+    let Ok(pos) = player.single() else {
+        return;
+    };
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        let GameEvent::ChooseItemAction { item: entity, action } = *event else {
+            continue;
+        };
+        let Some(definition) = items.get(entity).ok().and_then(|i| i.definition()) else {
+            continue;
+        };
+        next_state.set(GameState::InGame);
+        match action {
+            ItemAction::Throw => {
+                events_to_send.push(GameEvent::StartTargeting(TargetingPurpose::Throw(entity)));
+            }
+            ItemAction::Use if definition.area.needs_target() => {
+                events_to_send.push(GameEvent::StartTargeting(TargetingPurpose::UseItem(entity)));
+            }
+            ItemAction::Use => {
+                events_to_send.push(GameEvent::PlayerUseItem {
+                    item: entity,
+                    x: pos.x,
+                    y: pos.y,
+                });
+            }
+            ItemAction::Equip => events_to_send.push(GameEvent::PlayerEquip(entity)),
+            ItemAction::Remove => events_to_send.push(GameEvent::PlayerUnequip(entity)),
+            ItemAction::Drop => events_to_send.push(GameEvent::PlayerDrop(entity)),
+        }
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+}
+
+/// Uses items: drinking potions, reading scrolls and zapping wands. Effects aimed at a target
+/// travel along the line of fire and take place where they hit something. Using an item spends
+/// one charge, or one item from the stack. The player learns what an item is by using it.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn use_item_system(
+    cgd: Res<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    mut known: ResMut<KnownItems>,
+    mut commands: Commands,
+    mut effects: EventWriter<EffectEvent>,
+    users: Query<(Entity, &PerformAction, &Position, Has<Player>)>,
+    mut items: Query<(&Item, &DetailedName, Option<&mut Stack>, Option<&mut Charges>)>,
+    occupants: Query<&Position, With<Stats>>,
+) {
+    for (user, action, pos, is_player) in users.iter() {
+        let PerformAction::UseItem { item, x, y } = *action else {
+            continue;
+        };
+        let Ok((item_data, name, stack, charges)) = items.get_mut(item) else {
+            continue;
+        };
+        let Some(definition) = item_data.definition() else {
+            continue;
+        };
+        if is_player {
+            log.add(definition.use_message(known.shown_name(item_data, name)));
+            if known.identify(&definition.name) {
+                log.add(format!("This is a {}.", definition.name));
+            }
+        }
+
+        let tiles = definition
+            .area
+            .affected_tiles(&cgd.maps.map[pos.map], (pos.x, pos.y), (x, y), |x, y| {
+                occupants.iter().any(|p| p.map == pos.map && p.x == x && p.y == y)
+            });
+        for (tx, ty) in tiles {
+            for effect in definition.effects.iter() {
+                effects.write(EffectEvent {
+                    source: user,
+                    effect: effect.clone(),
+                    pos: Position {
+                        x: tx,
+                        y: ty,
+                        map: pos.map,
+                    },
+                });
+            }
+        }
+
+        let charged = charges.is_some();
+        if consume(&mut commands, item, stack, charges) && charged && is_player {
+            log.add(format!("The {} crumbles to dust.", definition.name));
+        }
+    }
+}
+
+/// Drops items on the floor where the one carrying them stands. Equipped items are taken off
+/// first, unless they are cursed.
+#[allow(clippy::type_complexity)]
+pub fn drop_system(
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    known: Res<KnownItems>,
+    users: Query<(&PerformAction, &Position, Has<Player>)>,
+    items: Query<(&Item, &DetailedName, Option<&Buc>, Has<Equipped>)>,
+) {
+    for (action, pos, is_player) in users.iter() {
+        let PerformAction::Drop { item } = *action else {
+            continue;
+        };
+        let Ok((item_data, name, buc, equipped)) = items.get(item) else {
+            continue;
+        };
+        let Some(definition) = item_data.definition() else {
+            continue;
+        };
+        let name = known.shown_name(item_data, name);
+        if equipped && buc == Some(&Buc::Cursed) {
+            commands.entity(item).insert(BucKnown);
+            if is_player {
+                log.add(format!("You can't take off the {}. It is cursed!", name));
+            }
+            continue;
+        }
+        commands
+            .entity(item)
+            .remove::<(Equipped, CarriedBy)>()
+            .insert((*pos, item_render(&definition)));
+        if is_player {
+            log.add(format!("You drop the {}.", name));
+        }
+    }
+}
+
+/// Picks up the items lying where the one picking them up stands. Items which stack with one
+/// already carried are added to that stack.
+#[allow(clippy::type_complexity)]
+pub fn pickup_system(
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    known: Res<KnownItems>,
+    users: Query<(Entity, &PerformAction, &Position, Has<Player>)>,
+    floor_items: Query<(Entity, &Item, &DetailedName, &Stack, Option<&Buc>, Has<Charges>, &Position)>,
+    mut carried: Query<(&Item, &mut Stack, Option<&Buc>, Has<BucKnown>, Has<Charges>, &CarriedBy), Without<Position>>,
+) {
+    for (user, action, pos, is_player) in users.iter() {
+        let PerformAction::PickUp = *action else {
+            continue;
+        };
+        let here = floor_items
+            .iter()
+            .filter(|(.., p)| p.map == pos.map && p.x == pos.x && p.y == pos.y);
+        for (entity, item, name, stack, buc, charged, _) in here {
+            // Stacks only merge if nothing tells them apart: charges, or a known blessing or curse.
+            let stackable = !charged && item.definition().is_some_and(|d| !d.equippable());
+            let existing = carried
+                .iter_mut()
+                .find(|(other, _, other_buc, buc_known, other_charged, carrier)| {
+                    carrier.0 == user && other.name == item.name && *other_buc == buc && !buc_known && !other_charged
+                });
+            match existing {
+                Some((_, mut carried_stack, ..)) if stackable => {
+                    carried_stack.count += stack.count;
+                    commands.entity(entity).despawn();
+                }
+                _ => {
+                    commands.entity(entity).remove::<(Position, Render)>().insert(CarriedBy(user));
+                }
+            }
+            if !is_player {
+                continue;
+            }
+            let shown = known.shown_name(item, name);
+            if stack.count > 1 {
+                log.add(format!("You pick up {}.", item_display_name(shown, Some(stack), None, None)));
+            } else {
+                log.add(format!("You pick up the {}.", shown));
+            }
+        }
+    }
+}
+
+/// Lets dying monsters drop something from the loot table of their level, by the level's chance.
+pub fn loot_drop_system(mut events: EventReader<LootDropEvent>, mut commands: Commands, known: Res<KnownItems>) {
+    for event in events.read() {
+        let depth = event.pos.map;
+        let dropped = {
+            let loot = &CFG.lock().unwrap().config.loot;
+            loot.level(depth)
+                .filter(|level| rng::range(0, 100) < level.drop_chance)
+                .and_then(|level| loot.roll(&level.table, depth))
+        };
+        let Some((name, count)) = dropped else {
+            continue;
+        };
+        let Some((definition, components, charges)) = new_item(&name, count, Some(&known)) else {
+            continue;
+        };
+        debug!("Dropping {} x {} at {:?}", count, name, event.pos);
+        let mut item = commands.spawn((components, event.pos, item_render(&definition)));
+        if let Some(charges) = charges {
+            item.insert(charges);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use crate::{
+    KEY_BINDINGS_NAME, UIComponent, UIComponentData, UIComponents, UIConfig,
+    config::{KeyBindingChanges, user_keybindings_path},
+    game_event::GameEvent,
+    system::input::KeyCapture,
+    ui::components::KeyBindingEditor,
+};
+
+pub fn show_keybinding_editor(uiconfig: Res<UIConfig>, mut uicomps: ResMut<UIComponents>) {
+    let editor = KeyBindingEditor::new(uiconfig.keybindings.clone());
+    uicomps.comps.insert(
+        KEY_BINDINGS_NAME.to_string(),
+        UIComponentData {
+            component: Box::new(editor) as Box<dyn UIComponent>,
+            visible: true,
+            layer: 1,
+        },
+    );
+}
+
+/// Leaving the editor throws away the changes which weren't saved.
+pub fn hide_keybinding_editor(mut uicomps: ResMut<UIComponents>, mut commands: Commands) {
+    uicomps.comps.remove(KEY_BINDINGS_NAME);
+    commands.remove_resource::<KeyCapture>();
+}
+
+/// Captures keys for the key binding editor, and saves the bindings which differ from the config
+/// files to the user's config. Saved bindings are used right away.
+pub fn keybinding_editor_system(
+    mut events: EventReader<GameEvent>,
+    mut uiconfig: ResMut<UIConfig>,
+    mut uicomps: ResMut<UIComponents>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        match event {
+            GameEvent::StartKeyCapture => commands.insert_resource(KeyCapture),
+            GameEvent::SaveKeyBindings => {
+                let Some(editor) = uicomps
+                    .comps
+                    .get_mut(KEY_BINDINGS_NAME)
+                    .and_then(|c| c.component.downcast_mut::<KeyBindingEditor>())
+                else {
+                    continue;
+                };
+                let changes = KeyBindingChanges::between(&uiconfig.default_keybindings, editor.bindings());
+                let result = match user_keybindings_path() {
+                    Some(path) => changes.save(&path).map(|_| path.display().to_string()),
+                    None => Err(anyhow::anyhow!("there is no config directory, set $XDG_CONFIG_HOME or $HOME")),
+                };
+                match &result {
+                    Ok(path) => {
+                        info!("Saved key bindings to {path}");
+                        uiconfig.keybindings = editor.bindings().clone();
+                    }
+                    Err(e) => error!("Failed to save key bindings: {e}"),
+                }
+                editor.saved(result);
+            }
+            _ => {}
+        }
+    }
+}
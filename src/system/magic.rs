@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    CFG, CurrentGameData, GameState, SPELL_MENU_NAME, UIComponent, UIComponentData, UIComponents,
+    component::{Attributes, KnownSpells, PerformAction, Player, Position, Skills, Stats},
+    effect::EffectEvent,
+    game_event::GameEvent,
+    gamelog::GameLog,
+    gamelogic::{CheckModifiers, Rollable},
+    targeting::TargetingPurpose,
+    ui::components::Menu,
+};
+
+/// Builds the spell menu from the spells the player knows, and shows it.
+pub fn show_spell_menu(mut uicomps: ResMut<UIComponents>, player: Query<&KnownSpells, With<Player>>) {
+    let cfg = &CFG.lock().unwrap().config.spells;
+    let mut menu = Menu::new();
+    menu.set_title("Cast which spell?");
+    let known = player.single().map(|k| k.spells.clone()).unwrap_or_default();
+    for name in known.iter() {
+        match cfg.id(name).and_then(|id| cfg.get(id).map(|spell| (id, spell))) {
+            Some((id, spell)) => {
+                menu.add_item((
+                    format!("{} ({} MP)", spell.name, spell.cost),
+                    Some(GameEvent::SelectSpell(id)),
+                ));
+            }
+            None => warn!("Player knows unknown spell '{}'", name),
+        }
+    }
+    if known.is_empty() {
+        menu.add_item(("You don't know any spells.", Some(GameEvent::ReturnToGame)));
+    }
+
+    uicomps.comps.insert(
+        SPELL_MENU_NAME.to_string(),
+        UIComponentData {
+            component: Box::new(menu) as Box<dyn UIComponent>,
+            visible: true,
+            layer: 1,
+        },
+    );
+}
+
+pub fn hide_spell_menu(mut uicomps: ResMut<UIComponents>) {
+    if let Some(c) = uicomps.comps.get_mut(SPELL_MENU_NAME) {
+        c.visible = false;
+    }
+}
+
+/// Handles the choice made in the spell menu: spells which need a target go through targeting
+/// mode first, the others are cast right away.
+pub fn select_spell_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut log: ResMut<GameLog>,
+    player: Query<(&Position, &Stats), With<Player>>,
+) {
+    let Ok((pos, stats)) = player.single() else {
+        return;
+    };
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        let GameEvent::SelectSpell(id) = *event else {
+            continue;
+        };
+        let cfg = &CFG.lock().unwrap().config.spells;
+        let Some(spell) = cfg.get(id) else {
+            continue;
+        };
+        next_state.set(GameState::InGame);
+        if stats.mp.current < spell.cost {
+            log.add(format!("You don't have enough mana to cast {}.", spell.name));
+        } else if spell.area.needs_target() {
+            events_to_send.push(GameEvent::StartTargeting(TargetingPurpose::Cast(id)));
+        } else {
+            events_to_send.push(GameEvent::PlayerCast {
+                spell: id,
+                x: pos.x,
+                y: pos.y,
+            });
+        }
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+}
+
+/// Casts spells. Casting costs the spell's magic points and succeeds on an INT check modified by
+/// the caster's magic skill and the difficulty of the spell. Targeted spells travel along the line
+/// of fire and take effect where they hit something.
+#[allow(clippy::type_complexity)]
+pub fn cast_spell_system(
+    cgd: Res<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    mut effects: EventWriter<EffectEvent>,
+    mut casters: Query<(
+        Entity,
+        &PerformAction,
+        &Position,
+        &Attributes,
+        Option<&Skills>,
+        &mut Stats,
+        Has<Player>,
+    )>,
+    occupants: Query<&Position, With<Stats>>,
+) {
+    let cfg = &CFG.lock().unwrap().config.spells;
+    for (caster, action, pos, attributes, skills, mut stats, is_player) in casters.iter_mut() {
+        let PerformAction::Cast { spell, x, y } = *action else {
+            continue;
+        };
+        let Some(spell) = cfg.get(spell) else {
+            continue;
+        };
+        if stats.mp.current < spell.cost {
+            continue;
+        }
+        stats.mp.decrease(spell.cost);
+
+        let modifiers = CheckModifiers::skill(skills.map_or(0, |s| s.magic)).with_difficulty(spell.difficulty);
+        if !attributes.int.roll_with(&modifiers).is_success() {
+            if is_player {
+                log.add(format!("You fail to cast {}.", spell.name));
+            }
+            continue;
+        }
+        if is_player {
+            log.add(format!("You cast {}.", spell.name));
+        }
+
+        let tiles = spell
+            .area
+            .affected_tiles(&cgd.maps.map[pos.map], (pos.x, pos.y), (x, y), |x, y| {
+                occupants.iter().any(|p| p.map == pos.map && p.x == x && p.y == y)
+            });
+        for (tx, ty) in tiles {
+            for effect in spell.effects.iter() {
+                effects.write(EffectEvent {
+                    source: caster,
+                    effect: effect.clone(),
+                    pos: Position {
+                        x: tx,
+                        y: ty,
+                        map: pos.map,
+                    },
+                });
+            }
+        }
+    }
+}
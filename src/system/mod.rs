@@ -1,9 +1,43 @@
 pub mod action;
+pub mod activity;
+pub mod combat;
+pub mod door;
+pub mod effect;
+pub mod equipment;
+pub mod experience;
+pub mod input;
 pub mod intent;
+pub mod inventory;
+pub mod item;
+pub mod keybindings;
+pub mod magic;
 pub mod player;
+pub mod regen;
+pub mod reload;
+pub mod status;
+pub mod targeting;
+pub mod terrain;
 pub mod ui_render;
+pub mod visibility;
 
 pub use action::*;
+pub use activity::*;
+pub use combat::*;
+pub use door::*;
+pub use effect::*;
+pub use equipment::*;
+pub use experience::*;
+pub use input::*;
 pub use intent::*;
+pub use inventory::*;
+pub use item::*;
+pub use keybindings::*;
+pub use magic::*;
 pub use player::*;
+pub use regen::*;
+pub use reload::*;
+pub use status::*;
+pub use targeting::*;
+pub use terrain::*;
 pub use ui_render::*;
+pub use visibility::*;
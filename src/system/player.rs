@@ -1,7 +1,8 @@
 use crate::{
     CurrentGameData, TurnState,
     component::{Energy, Player, Position, Speed},
-    event::{PlayerMoveRelativeEvent, PlayerSpentEnergy},
+    event::{GameTick, PlayerMoveRelativeEvent, PlayerSpentEnergy},
+    game::GameTime,
 };
 use bevy::prelude::*;
 
@@ -15,7 +16,6 @@ pub fn update_player_pos(mut cgd: ResMut<CurrentGameData>, query: Query<&Positio
 pub fn player_move_system(
     mut player_move: EventReader<PlayerMoveRelativeEvent>,
     mut query: Query<&mut Position, With<Player>>,
-    mut next_state: ResMut<NextState<TurnState>>,
 ) {
     for pm in player_move.read() {
         debug_once!("Got PlayerMoveRelativeEvent, moving player");
@@ -23,15 +23,19 @@ pub fn player_move_system(
             pos.x += pm.dx;
             pos.y += pm.dy;
         }
-        next_state.set(TurnState::NotPlayersTurn);
     }
 }
 
+/// When the player spends energy, game time passes and all other entities on the map gain energy.
+/// Spending energy ends the player's turn.
 pub fn player_spent_energy_system(
     cgd: Res<CurrentGameData>,
+    mut time: ResMut<GameTime>,
     mut energy_queue: EventReader<PlayerSpentEnergy>,
+    mut tick_queue: EventWriter<GameTick>,
     mut energy_query: Query<(&mut Energy, &Position)>,
     player_query: Query<&Speed, With<Player>>,
+    mut next_state: ResMut<NextState<TurnState>>,
 ) {
     if let Ok(speed) = player_query.single() {
         for e in energy_queue.read() {
@@ -43,6 +47,10 @@ pub fn player_spent_energy_system(
                     debug!("Energy component after increase: {:?}", energy);
                 }
             }
+            for tick in time.advance(e.0) {
+                tick_queue.write(GameTick(tick));
+            }
+            next_state.set(TurnState::NotPlayersTurn);
         }
     }
 }
@@ -0,0 +1,102 @@
+use crate::{
+    CurrentGameData,
+    component::{Attribute, Attributes, Enemy, InCombat, Player, Position, Regen, Regeneration, Stats},
+    event::GameTick,
+    status::Poisoned,
+};
+use bevy::prelude::*;
+
+/// Returns the number of ticks between each regeneration, given the governing attribute.
+/// Each point of the attribute shortens the base frequency by one percent, relative to 100.
+pub fn regen_interval(regen: &Regen, attribute: &Attribute) -> u64 {
+    let value = attribute.value().max(0);
+    (regen.frequency * 100 / (100 + value)).max(1) as u64
+}
+
+fn regenerates_on(regen: &Regen, attribute: &Attribute, tick: u64) -> bool {
+    regen.frequency > 0 && regen.amount > 0 && tick.is_multiple_of(regen_interval(regen, attribute))
+}
+
+/// Regenerates hit points and magic points for entities with a `Regeneration` component.
+/// Nothing regenerates while an entity is in combat or poisoned.
+#[allow(clippy::type_complexity)]
+pub fn regeneration_system(
+    mut tick_queue: EventReader<GameTick>,
+    mut query: Query<(&Regeneration, &Attributes, &mut Stats), (Without<InCombat>, Without<Poisoned>)>,
+) {
+    for GameTick(tick) in tick_queue.read() {
+        for (regeneration, attributes, mut stats) in query.iter_mut() {
+            if regenerates_on(&regeneration.hp, &attributes.con, *tick) {
+                stats.hp.increase(regeneration.hp.amount);
+            }
+            if regenerates_on(&regeneration.mp, &attributes.int, *tick) {
+                stats.mp.increase(regeneration.mp.amount);
+            }
+        }
+    }
+}
+
+/// Marks the player and any enemies the player can see as being in combat.
+/// Everyone else is taken out of combat.
+pub fn combat_status_system(
+    cgd: Res<CurrentGameData>,
+    mut commands: Commands,
+    player_query: Query<(Entity, Has<InCombat>), With<Player>>,
+    enemy_query: Query<(Entity, &Position, Has<InCombat>), With<Enemy>>,
+) {
+    let map = &cgd.maps.map[cgd.current_map];
+    let mut enemy_in_view = false;
+
+    for (entity, pos, in_combat) in enemy_query.iter() {
+        let visible = pos.map == cgd.current_map && map.is_visible(pos.x as usize, pos.y as usize);
+        enemy_in_view |= visible;
+        set_in_combat(&mut commands, entity, in_combat, visible);
+    }
+
+    if let Ok((entity, in_combat)) = player_query.single() {
+        set_in_combat(&mut commands, entity, in_combat, enemy_in_view);
+    }
+}
+
+fn set_in_combat(commands: &mut Commands, entity: Entity, in_combat: bool, should_be: bool) {
+    if should_be && !in_combat {
+        commands.entity(entity).insert(InCombat);
+    } else if !should_be && in_combat {
+        commands.entity(entity).remove::<InCombat>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(base: i32) -> Attribute {
+        Attribute {
+            base,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn higher_attribute_regenerates_more_often() {
+        let regen = Regen {
+            frequency: 20,
+            amount: 1,
+        };
+        assert_eq!(regen_interval(&regen, &attribute(0)), 20);
+        assert_eq!(regen_interval(&regen, &attribute(25)), 16);
+        assert_eq!(regen_interval(&regen, &attribute(100)), 10);
+    }
+
+    #[test]
+    fn regen_interval_is_never_zero() {
+        let regen = Regen { frequency: 1, amount: 1 };
+        assert_eq!(regen_interval(&regen, &attribute(90)), 1);
+    }
+
+    #[test]
+    fn zero_frequency_never_regenerates() {
+        let regen = Regen { frequency: 0, amount: 1 };
+        assert!(!regenerates_on(&regen, &attribute(10), 20));
+    }
+}
@@ -0,0 +1,351 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    CFG, CurrentGameData, UIComponents, UIConfig,
+    config::{self, Config, KeyBindings, key_sequence_to_string, paths, user_keybindings_path},
+};
+
+/// How often the watched files are checked for changes, in seconds.
+const WATCH_INTERVAL: f32 = 1.0;
+
+const UI_CONFIG_FILES: [&str; 6] = [
+    "ui_config.json5",
+    "ui_config.json",
+    "ui_config.ron",
+    "ui_config.yaml",
+    "ui_config.toml",
+    "ui_config.ini",
+];
+
+/// Watches the config and data files while the game runs, so changes to them are used without
+/// restarting. Only present when the game is started with `--watch`.
+#[derive(Resource)]
+pub struct ConfigWatcher {
+    timer: Timer,
+    /// When each file was last modified, None if it doesn't exist (so creating it counts as a change).
+    modified: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Self {
+        Self {
+            timer: Timer::from_seconds(WATCH_INTERVAL, TimerMode::Repeating),
+            modified: modification_times(&watched_files()),
+        }
+    }
+}
+
+impl Default for ConfigWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The files which can change the config: the UI config and RON files in every config and data
+/// directory, and the key bindings saved from the game.
+fn watched_files() -> Vec<PathBuf> {
+    let paths = paths();
+    let config_files = UI_CONFIG_FILES.iter().chain(&["game.ron", "player.ron"]);
+    let data_files = [
+        "world.ron",
+        "experience.ron",
+        "spells.ron",
+        "items.ron",
+        "loot.ron",
+        "tiles.ron",
+    ];
+    let mut files: Vec<PathBuf> = paths
+        .config_dirs
+        .iter()
+        .flat_map(|dir| config_files.clone().map(|file| dir.join(file)))
+        .chain(paths.data_dirs.iter().flat_map(|dir| data_files.map(|file| dir.join(file))))
+        .collect();
+    files.extend(user_keybindings_path());
+    files
+}
+
+fn modification_times(files: &[PathBuf]) -> HashMap<PathBuf, Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| (file.clone(), fs::metadata(file).and_then(|m| m.modified()).ok()))
+        .collect()
+}
+
+/// The files which were created, changed or removed between two checks, sorted.
+pub fn changed_files(
+    before: &HashMap<PathBuf, Option<SystemTime>>,
+    after: &HashMap<PathBuf, Option<SystemTime>>,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(file, modified)| before.get(*file).is_none_or(|m| m != *modified))
+        .map(|(file, _)| file.clone())
+        .collect();
+    changed.sort();
+    changed
+}
+
+fn is_ui_config(file: &Path) -> bool {
+    let name = file.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    UI_CONFIG_FILES.contains(&name) || Some(file) == user_keybindings_path().as_deref()
+}
+
+/// Describes how the key bindings changed, one line per binding added, removed or bound to
+/// something else.
+pub fn binding_changes(before: &KeyBindings, after: &KeyBindings) -> Vec<String> {
+    let contexts: BTreeMap<_, _> = before.keys().chain(after.keys()).map(|c| (c.to_string(), c)).collect();
+    let mut changes = Vec::new();
+    for (name, context) in contexts {
+        let (old, new) = (before.get(context), after.get(context));
+        let keys: BTreeMap<_, _> = old
+            .into_iter()
+            .chain(new)
+            .flat_map(|bindings| bindings.keys())
+            .map(|keys| (key_sequence_to_string(keys), keys))
+            .collect();
+        for (shown, keys) in keys {
+            match (old.and_then(|b| b.get(keys)), new.and_then(|b| b.get(keys))) {
+                (None, Some(event)) => changes.push(format!("{name} {shown}: bound to {event:?}")),
+                (Some(event), None) => changes.push(format!("{name} {shown}: {event:?} unbound")),
+                (Some(old), Some(new)) if old != new => changes.push(format!("{name} {shown}: {old:?} -> {new:?}")),
+                _ => {}
+            }
+        }
+    }
+    changes
+}
+
+/// Describes which sections of the config changed, naming the items, spells and loot tables which
+/// were added, removed or changed, and the tile types whose definitions did.
+pub fn config_changes(before: &Config, after: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (section, changed) in [
+        ("game", before.game != after.game),
+        ("player", before.player != after.player),
+        ("world", before.world != after.world),
+        ("experience", before.experience != after.experience),
+        ("loot levels", before.loot.levels != after.loot.levels),
+        ("loot key", before.loot.key != after.loot.key),
+    ] {
+        if changed {
+            changes.push(format!("{section} changed"));
+        }
+    }
+    changes.extend(named_changes(
+        "items",
+        before.items.items.iter().map(|i| (i.name.clone(), i)),
+        after.items.items.iter().map(|i| (i.name.clone(), i)),
+    ));
+    changes.extend(named_changes(
+        "spells",
+        before.spells.spells.iter().map(|s| (s.name.clone(), s)),
+        after.spells.spells.iter().map(|s| (s.name.clone(), s)),
+    ));
+    changes.extend(named_changes(
+        "loot tables",
+        before.loot.tables.iter().map(|(n, t)| (n.clone(), t)),
+        after.loot.tables.iter().map(|(n, t)| (n.clone(), t)),
+    ));
+    changes.extend(named_changes(
+        "tiles",
+        before.tiles.tiles.iter().map(|t| (format!("{:?}", t.id), t)),
+        after.tiles.tiles.iter().map(|t| (format!("{:?}", t.id), t)),
+    ));
+    changes
+}
+
+/// Describes which entries of a section were added, removed or changed, if any were.
+fn named_changes<'a, T: PartialEq + 'a>(
+    section: &str,
+    before: impl Iterator<Item = (String, &'a T)>,
+    after: impl Iterator<Item = (String, &'a T)>,
+) -> Option<String> {
+    let (before, after): (BTreeMap<_, _>, BTreeMap<_, _>) = (before.collect(), after.collect());
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut described = Vec::new();
+    for name in names {
+        match (before.get(name), after.get(name)) {
+            (None, Some(_)) => described.push(format!("added `{name}`")),
+            (Some(_), None) => described.push(format!("removed `{name}`")),
+            (Some(old), Some(new)) if old != new => described.push(format!("changed `{name}`")),
+            _ => {}
+        }
+    }
+    (!described.is_empty()).then(|| format!("{section}: {}", described.join(", ")))
+}
+
+/// Reloads the config files which changed. Invalid changes are logged and ignored, keeping the
+/// config which was loaded before.
+pub fn config_reload_system(
+    time: Res<Time>,
+    mut watcher: ResMut<ConfigWatcher>,
+    mut uiconfig: ResMut<UIConfig>,
+    mut uicomps: ResMut<UIComponents>,
+    mut cgd: ResMut<CurrentGameData>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = modification_times(&watched_files());
+    let changed = changed_files(&watcher.modified, &modified);
+    if changed.is_empty() {
+        return;
+    }
+    watcher.modified = modified;
+    for file in &changed {
+        info!("Config file changed: {}", file.display());
+    }
+
+    let (ui_files, ron_files): (Vec<&PathBuf>, Vec<&PathBuf>) = changed.iter().partition(|f| is_ui_config(f));
+    if !ron_files.is_empty() {
+        let before = CFG.lock().unwrap().config.clone();
+        match config::load_config(paths()) {
+            Ok(()) => {
+                info!("Reloaded the config and data files");
+                let cfg = CFG.lock().unwrap();
+                for change in config_changes(&before, &cfg.config) {
+                    info!("Config changed: {change}");
+                }
+                // Maps keep the tile definitions they were made with, so they're given the new ones
+                if before.tiles != cfg.config.tiles {
+                    let definitions = Arc::new(cfg.config.tiles.clone());
+                    for map in cgd.maps.map.iter_mut() {
+                        map.set_definitions(definitions.clone());
+                    }
+                }
+            }
+            Err(report) => {
+                for problem in &report.problems {
+                    error!("{problem}");
+                }
+                warn!("Keeping the previous config and data, fix the problems above to reload them");
+            }
+        }
+    }
+    if !ui_files.is_empty() {
+        match UIConfig::new() {
+            Ok(new) => {
+                for change in binding_changes(&uiconfig.keybindings, &new.keybindings) {
+                    info!("Key binding changed: {change}");
+                }
+                if new.styles.0 != uiconfig.styles.0 {
+                    info!("Styles changed");
+                }
+                if new.layout != uiconfig.layout {
+                    info!("Layout changed: {:?}", new.layout);
+                }
+                for (name, c) in uicomps.comps.iter_mut() {
+                    if let Err(e) = c.component.register_config_handler(new.clone()) {
+                        error!("Failed to reconfigure {name}: {e:?}");
+                    }
+                }
+                *uiconfig = new;
+            }
+            Err(report) => {
+                for problem in &report.problems {
+                    error!("{problem}");
+                }
+                warn!("Keeping the previous UI config, fix the problems above to reload it");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ExperienceConfig, GameConfig, ItemsConfig, LootConfig, PlayerConfig, SpellsConfig, TilesConfig, WorldConfig,
+    };
+    use crate::{GameState, config::InputContext, config::parse_key_sequence, game_event::GameEvent};
+    use std::time::Duration;
+
+    #[test]
+    fn created_changed_and_removed_files_are_changes() {
+        let t = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let before = HashMap::from([
+            (PathBuf::from("same.ron"), t(1)),
+            (PathBuf::from("changed.ron"), t(1)),
+            (PathBuf::from("removed.ron"), t(1)),
+            (PathBuf::from("created.ron"), None),
+        ]);
+        let after = HashMap::from([
+            (PathBuf::from("same.ron"), t(1)),
+            (PathBuf::from("changed.ron"), t(2)),
+            (PathBuf::from("removed.ron"), None),
+            (PathBuf::from("created.ron"), t(2)),
+        ]);
+        let names = ["changed.ron", "created.ron", "removed.ron"];
+        assert_eq!(changed_files(&before, &after), names.map(PathBuf::from));
+        assert!(changed_files(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn binding_changes_are_described() {
+        let bindings = |pairs: &[(&str, GameEvent)]| {
+            let map = pairs
+                .iter()
+                .map(|(keys, event)| (parse_key_sequence(keys).unwrap(), event.clone()))
+                .collect();
+            KeyBindings(HashMap::from([(InputContext::State(GameState::InGame), map)]))
+        };
+        let before = bindings(&[
+            ("<q>", GameEvent::Quit),
+            ("<r>", GameEvent::PlayerRest),
+            ("<w>", GameEvent::PlayerWait),
+        ]);
+        let after = bindings(&[
+            ("<q>", GameEvent::Quit),
+            ("<r>", GameEvent::PlayerWait),
+            ("<x>", GameEvent::Quit),
+        ]);
+        assert_eq!(
+            binding_changes(&before, &after),
+            vec![
+                "InGame <r>: PlayerRest -> PlayerWait",
+                "InGame <w>: PlayerWait unbound",
+                "InGame <x>: bound to Quit",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_changes_name_what_changed() {
+        let before = Config {
+            game: GameConfig::new(),
+            player: PlayerConfig::new(),
+            world: WorldConfig::new(),
+            experience: ExperienceConfig::new(),
+            spells: SpellsConfig::new(),
+            items: ron::de::from_str::<ItemsConfig>(include_str!("../../data/items.ron")).unwrap(),
+            loot: LootConfig::new(),
+            tiles: ron::de::from_str::<TilesConfig>(include_str!("../../data/tiles.ron")).unwrap(),
+        };
+        assert!(config_changes(&before, &before).is_empty());
+
+        let mut after = before.clone();
+        after.world.default_map_width += 1;
+        after.items.items[0].range += 1;
+        after.items.items.pop();
+        let mut added = after.items.items[0].clone();
+        added.name = "potion of testing".to_string();
+        after.items.items.push(added);
+        after.tiles.tiles[0].glyph = '%';
+        assert_eq!(
+            config_changes(&before, &after),
+            vec![
+                "world changed".to_string(),
+                "items: removed `iron key`, changed `potion of healing`, added `potion of testing`".to_string(),
+                "tiles: changed `Wall`".to_string(),
+            ]
+        );
+    }
+}
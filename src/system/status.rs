@@ -0,0 +1,80 @@
+use crate::{
+    component::{Intent, Player, Stats},
+    event::GameTick,
+    gamelog::GameLog,
+    rng,
+    status::{Confused, Paralysed, StatusEffect},
+};
+use bevy::prelude::*;
+
+/// Counts down the duration of status effects of type T, applies their per-tick effects,
+/// and removes them when they expire.
+pub fn status_tick_system<T: StatusEffect>(
+    mut tick_queue: EventReader<GameTick>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut T, Option<&mut Stats>, Has<Player>)>,
+) {
+    for _ in tick_queue.read() {
+        for (entity, mut status, stats, player) in query.iter_mut() {
+            if status.duration() == 0 {
+                continue;
+            }
+            if let Some(mut stats) = stats {
+                status.tick(&mut stats);
+            }
+            *status.duration_mut() -= 1;
+            if status.duration() == 0 {
+                debug!("Status effect {} expired on {:?}", T::NAME, entity);
+                if player {
+                    log.add(status.expire_message());
+                }
+                commands.entity(entity).remove::<T>();
+            }
+        }
+    }
+}
+
+/// A random step in any of the eight directions, or staying put.
+fn confused_step() -> (i32, i32) {
+    // The upper bound is exclusive.
+    (rng::range(-1, 2), rng::range(-1, 2))
+}
+
+/// Lets status effects change what an entity intends to do.
+/// Paralysed entities can't do anything, and confused entities sometimes move in a random direction.
+#[allow(clippy::type_complexity)]
+pub fn status_intent_system(
+    mut log: ResMut<GameLog>,
+    mut query: Query<(&mut Intent, Has<Confused>, Has<Paralysed>, Has<Player>)>,
+) {
+    for (mut intent, confused, paralysed, player) in query.iter_mut() {
+        if paralysed {
+            if player && !matches!(*intent, Intent::Nothing) {
+                log.add("You can't move!");
+            }
+            *intent = Intent::Nothing;
+        } else if confused && matches!(*intent, Intent::MoveRelative { .. }) && rng::roll_dice(1, 2) == 1 {
+            let (dx, dy) = confused_step();
+            if player {
+                log.add("You stumble around in confusion.");
+            }
+            *intent = Intent::MoveRelative { dx, dy };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confused_steps_go_in_every_direction() {
+        let steps: Vec<(i32, i32)> = (0..200).map(|_| confused_step()).collect();
+        for d in [-1, 0, 1] {
+            assert!(steps.iter().any(|(dx, _)| *dx == d), "no step with dx {d}");
+            assert!(steps.iter().any(|(_, dy)| *dy == d), "no step with dy {d}");
+        }
+        assert!(steps.iter().all(|(dx, dy)| dx.abs() <= 1 && dy.abs() <= 1));
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use crate::{
+    CFG, CurrentGameData, GameState,
+    component::{Player, Position, Stats},
+    game_event::GameEvent,
+    gamelog::GameLog,
+    item::Item,
+    targeting::{Targeting, TargetingPurpose, line_of_fire},
+    utils,
+};
+
+/// Enters targeting mode, starting with the cursor on the nearest visible target in range.
+#[allow(clippy::type_complexity)]
+pub fn start_targeting_system(
+    mut events: EventReader<GameEvent>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    cgd: Res<CurrentGameData>,
+    candidates: Query<(Entity, &Position), (With<Stats>, Without<Player>)>,
+    items: Query<&Item>,
+) {
+    for event in events.read() {
+        let GameEvent::StartTargeting(purpose) = event else {
+            continue;
+        };
+        let range = match purpose {
+            TargetingPurpose::Throw(item)
+            | TargetingPurpose::UseItem(item)
+            | TargetingPurpose::Fire { weapon: item, .. } => {
+                items.get(*item).ok().and_then(|i| i.definition()).map_or(0, |d| d.range)
+            }
+            TargetingPurpose::Cast(spell) => CFG.lock().unwrap().config.spells.get(*spell).map_or(0, |s| s.range),
+        };
+        let origin = cgd.player_pos;
+        let map = &cgd.maps.map[origin.map];
+        let distance = |pos: &Position| utils::distance2d_chebyshev((origin.x, origin.y), (pos.x, pos.y)) as i32;
+
+        let mut targets: Vec<(Entity, Position)> = candidates
+            .iter()
+            .filter(|(_, pos)| pos.map == origin.map && map.is_visible(pos.x as usize, pos.y as usize))
+            .filter(|(_, pos)| distance(pos) <= range)
+            .map(|(e, pos)| (e, *pos))
+            .collect();
+        targets.sort_by_key(|(_, pos)| distance(pos));
+
+        debug!("Targeting for {:?} with {} targets", purpose, targets.len());
+        commands.insert_resource(Targeting::new(*purpose, range, origin, targets));
+        next_state.set(GameState::Targeting);
+    }
+}
+
+/// Moves the targeting cursor and acts on the chosen target.
+#[allow(clippy::type_complexity)]
+pub fn targeting_system(
+    mut param_set: ParamSet<(EventReader<GameEvent>, EventWriter<GameEvent>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut targeting: ResMut<Targeting>,
+    mut log: ResMut<GameLog>,
+    cgd: Res<CurrentGameData>,
+    occupants: Query<(&Position, Option<&Name>), (With<Stats>, Without<Player>)>,
+) {
+    let map = &cgd.maps.map[targeting.origin.map];
+    let mut events_to_send = Vec::new();
+    for event in param_set.p0().read() {
+        match event {
+            GameEvent::MoveTargetCursor { dx, dy } => targeting.move_cursor(map, *dx, *dy),
+            GameEvent::NextTarget => targeting.cycle(1),
+            GameEvent::PrevTarget => targeting.cycle(-1),
+            GameEvent::ConfirmTarget => {
+                let (x, y) = (targeting.cursor.x, targeting.cursor.y);
+                if (x, y) == (targeting.origin.x, targeting.origin.y) {
+                    log.add("You can't target yourself.");
+                    continue;
+                }
+                match targeting.purpose {
+                    TargetingPurpose::Throw(item) => {
+                        events_to_send.push(GameEvent::PlayerThrow { item, x, y });
+                    }
+                    TargetingPurpose::UseItem(item) => {
+                        events_to_send.push(GameEvent::PlayerUseItem { item, x, y });
+                    }
+                    TargetingPurpose::Cast(spell) => {
+                        events_to_send.push(GameEvent::PlayerCast { spell, x, y });
+                    }
+                    TargetingPurpose::Fire { weapon, ammo } => {
+                        events_to_send.push(GameEvent::PlayerFire { weapon, ammo, x, y });
+                    }
+                }
+                next_state.set(GameState::InGame);
+            }
+            _ => {}
+        }
+    }
+    for event in events_to_send {
+        param_set.p1().write(event);
+    }
+
+    if !targeting.is_changed() {
+        return;
+    }
+    let occupant_at = |x: i32, y: i32| {
+        occupants
+            .iter()
+            .find(|(pos, _)| pos.map == targeting.origin.map && pos.x == x && pos.y == y)
+    };
+    let (origin, cursor) = (targeting.origin, targeting.cursor);
+    let path = line_of_fire(map, (origin.x, origin.y), (cursor.x, cursor.y), |x, y| {
+        occupant_at(x, y).is_some()
+    });
+    let visible = map.is_visible(cursor.x as usize, cursor.y as usize);
+    let description = match occupant_at(cursor.x, cursor.y) {
+        Some((_, name)) if visible => name.map_or("something".to_string(), |n| n.to_string()),
+        _ if visible => "nothing".to_string(),
+        _ => "unseen".to_string(),
+    };
+
+    // Derived data only, so don't trigger another recalculation next frame.
+    let targeting = targeting.bypass_change_detection();
+    targeting.path = path;
+    targeting.description = description;
+}
+
+pub fn cleanup_targeting(mut commands: Commands) {
+    commands.remove_resource::<Targeting>();
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+
+use crate::{
+    CurrentGameData, GameState,
+    component::{Attributes, PerformAction, Player, Position},
+    event::MapChanged,
+    game_event::GameEvent,
+    gamelog::GameLog,
+    map::TileType,
+    rng,
+    system::{
+        door::DirectionPrompt,
+        equipment::{EquippedItems, wielded_equipment},
+    },
+};
+
+/// What is logged when a tile is destroyed.
+pub fn destroyed_message(tile: TileType, name: &str) -> String {
+    match tile {
+        TileType::Wall => format!("The {name} collapses."),
+        _ => format!("The {name} breaks apart."),
+    }
+}
+
+/// Handles the bash command by asking the player for the direction.
+pub fn bash_prompt_system(
+    mut events: EventReader<GameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut log: ResMut<GameLog>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if *event == GameEvent::Bash {
+            log.add("Which direction do you want to bash?");
+            commands.insert_resource(DirectionPrompt::Bash);
+            next_state.set(GameState::ChooseDirection);
+        }
+    }
+}
+
+/// Bashes walls and doors. Wielding a weapon which can dig uses its digging dice, otherwise the
+/// weapon's damage and strength bonus are used as for melee attacks. Tiles which lose all their
+/// hitpoints are destroyed, walls leave rubble behind.
+pub fn bash_system(
+    mut cgd: ResMut<CurrentGameData>,
+    mut log: ResMut<GameLog>,
+    mut changes: EventWriter<MapChanged>,
+    users: Query<(Entity, &PerformAction, &Position, &Attributes, Has<Player>)>,
+    equipped: EquippedItems,
+) {
+    for (user, action, pos, attributes, is_player) in users.iter() {
+        let PerformAction::Bash { x, y } = *action else {
+            continue;
+        };
+        let map = &mut cgd.maps.map[pos.map];
+        let tile = map.get_tile_type(x as usize, y as usize);
+        let name = map.definition(tile).name.clone();
+        if !map.is_destructable(x as usize, y as usize) {
+            if is_player {
+                log.add(format!("The {name} doesn't budge."));
+            }
+            continue;
+        }
+
+        let weapon = wielded_equipment(user, &equipped);
+        let (damage, message) = match weapon.as_ref().and_then(|w| w.digging.as_ref()) {
+            Some(digging) => (rng::roll_str(digging), format!("You dig into the {name}.")),
+            None => {
+                let dice = weapon.and_then(|w| w.damage).unwrap_or_else(|| "1d4".to_string());
+                let damage = rng::roll_str(&dice) + attributes.str.value() / 20;
+                (damage, format!("You bash the {name}."))
+            }
+        };
+        let destroyed = map.damage_tile(x, y, damage.max(1));
+        if is_player {
+            log.add(message);
+            if destroyed {
+                log.add(destroyed_message(tile, &name));
+            }
+        }
+        if destroyed {
+            changes.write(MapChanged { map: pos.map });
+        }
+    }
+}
@@ -1,12 +1,39 @@
 use bevy::{log::error, prelude::Result};
-use bevy_ecs::system::ResMut;
-use bevy_ratatui::terminal::RatatuiContext;
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    event::{EventReader, EventWriter},
+    system::ResMut,
+};
+use bevy_ratatui::{event::ResizeEvent, terminal::RatatuiContext};
+use ratatui::{Terminal, backend::Backend};
 
+use crate::game_event::GameEvent;
 pub use crate::ui::UIComponents;
 
+/// Passes terminal resizes on to the UI components as game events. Only the last size of a frame
+/// matters, so bursts of resizes while dragging the window are relayouted once.
+pub fn resize_system(mut resizes: EventReader<ResizeEvent>, mut events: EventWriter<GameEvent>) {
+    if let Some(ResizeEvent(size)) = resizes.read().last() {
+        events.write(GameEvent::Resize(size.width, size.height));
+    }
+}
+
 pub fn ui_render_system(mut context: ResMut<RatatuiContext>, mut ui_components: ResMut<UIComponents>) -> Result {
-    context.draw(|f| {
-        for (_component_name, uicomponent) in ui_components.comps.iter_mut().filter(|x| x.1.visible) {
+    draw_components(&mut context, &mut ui_components)
+}
+
+/// Draws the visible components, lowest layer first. Drawing doesn't count as changing them, so it
+/// doesn't cause another redraw on the next frame.
+fn draw_components<B: Backend>(terminal: &mut Terminal<B>, ui_components: &mut ResMut<UIComponents>) -> Result {
+    terminal.draw(|f| {
+        let mut visible: Vec<_> = ui_components
+            .bypass_change_detection()
+            .comps
+            .iter_mut()
+            .filter(|x| x.1.visible)
+            .collect();
+        visible.sort_by_key(|(_, uicomponent)| uicomponent.layer);
+        for (_component_name, uicomponent) in visible {
             // log::debug!("Drawing component: {}", component_name);
             let r = uicomponent.component.draw(f, f.area());
             if let Err(e) = r {
@@ -18,3 +45,59 @@ pub fn ui_render_system(mut context: ResMut<RatatuiContext>, mut ui_components:
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UIComponent, UIComponentData};
+    use bevy::prelude::*;
+    use ratatui::{Frame, backend::TestBackend, layout::Rect};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Resource)]
+    struct TestTerminal(Terminal<TestBackend>);
+
+    struct CountDraws(Arc<AtomicUsize>);
+
+    impl UIComponent for CountDraws {
+        fn draw(&mut self, _f: &mut Frame<'_>, _area: Rect) -> anyhow::Result<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn test_render_system(mut terminal: ResMut<TestTerminal>, mut ui_components: ResMut<UIComponents>) -> Result {
+        draw_components(&mut terminal.0, &mut ui_components)
+    }
+
+    #[test]
+    fn frames_without_changes_are_not_drawn() {
+        let draws = Arc::new(AtomicUsize::new(0));
+        let mut ui_components = UIComponents::default();
+        ui_components.comps.insert(
+            "counter".to_string(),
+            UIComponentData {
+                component: Box::new(CountDraws(draws.clone())),
+                visible: true,
+                layer: 0,
+            },
+        );
+        let mut app = App::new();
+        app.insert_resource(TestTerminal(Terminal::new(TestBackend::new(20, 10)).unwrap()))
+            .insert_resource(ui_components)
+            .add_systems(Update, test_render_system.run_if(resource_changed::<UIComponents>));
+
+        app.update();
+        assert_eq!(draws.load(Ordering::Relaxed), 1);
+        app.update();
+        app.update();
+        assert_eq!(draws.load(Ordering::Relaxed), 1);
+
+        app.world_mut().resource_mut::<UIComponents>().set_changed();
+        app.update();
+        assert_eq!(draws.load(Ordering::Relaxed), 2);
+    }
+}
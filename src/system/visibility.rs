@@ -0,0 +1,45 @@
+use crate::{
+    CurrentGameData,
+    component::{Player, Position, Viewshed},
+    event::MapChanged,
+    map::field_of_view,
+    status::Blinded,
+};
+use bevy::prelude::*;
+
+/// Recalculates viewsheds for entities which have moved or been marked dirty.
+/// The player's viewshed also updates which tiles on the map are visible and revealed.
+#[allow(clippy::type_complexity)]
+pub fn visibility_system(
+    mut cgd: ResMut<CurrentGameData>,
+    mut query: Query<(Ref<Position>, &mut Viewshed, Option<&Player>, Has<Blinded>)>,
+) {
+    for (pos, mut viewshed, player, blinded) in query.iter_mut() {
+        if !viewshed.dirty && !pos.is_changed() {
+            continue;
+        }
+
+        let map = &mut cgd.maps.map[pos.map];
+        let range = if blinded { 1 } else { viewshed.range };
+        viewshed.visible_tiles = field_of_view(map, pos.x, pos.y, range);
+        viewshed.dirty = false;
+
+        if player.is_some() {
+            map.clear_visible();
+            for (x, y) in viewshed.visible_tiles.iter() {
+                map.set_visible(*x, *y, true);
+                map.set_revealed(*x, *y, true);
+            }
+        }
+    }
+}
+
+/// Marks the viewsheds of everything on a map whose tiles changed as dirty, since what blocks the
+/// view may have changed.
+pub fn map_changed_system(mut events: EventReader<MapChanged>, mut viewsheds: Query<(&Position, &mut Viewshed)>) {
+    for event in events.read() {
+        for (_, mut viewshed) in viewsheds.iter_mut().filter(|(p, _)| p.map == event.map) {
+            viewshed.dirty = true;
+        }
+    }
+}
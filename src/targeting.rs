@@ -0,0 +1,155 @@
+use bevy_ecs::prelude::*;
+
+use crate::{component::Position, map::Map, utils};
+
+/// What the player is choosing a target for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetingPurpose {
+    /// Throwing the given item.
+    Throw(Entity),
+    /// Using (e.g. zapping) the given item.
+    UseItem(Entity),
+    /// Casting the spell with the given id.
+    Cast(usize),
+    /// Firing the ammo from the wielded launcher.
+    Fire { weapon: Entity, ammo: Entity },
+}
+
+impl TargetingPurpose {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TargetingPurpose::Throw(_) => "Throw",
+            TargetingPurpose::UseItem(_) => "Use",
+            TargetingPurpose::Cast(_) => "Cast",
+            TargetingPurpose::Fire { .. } => "Fire",
+        }
+    }
+}
+
+/// State of the targeting mode. Only exists while in `GameState::Targeting`.
+#[derive(Resource, Debug)]
+pub struct Targeting {
+    pub purpose: TargetingPurpose,
+    /// How far away (in tiles) a target may be.
+    pub range: i32,
+    pub origin: Position,
+    pub cursor: Position,
+    /// Visible potential targets, nearest first.
+    pub targets: Vec<(Entity, Position)>,
+    pub index: usize,
+    /// The tiles a projectile would pass through on its way to the cursor.
+    pub path: Vec<(i32, i32)>,
+    /// Short description of whatever is under the cursor.
+    pub description: String,
+}
+
+impl Targeting {
+    pub fn new(purpose: TargetingPurpose, range: i32, origin: Position, targets: Vec<(Entity, Position)>) -> Self {
+        let cursor = targets.first().map_or(origin, |(_, pos)| *pos);
+        Self {
+            purpose,
+            range,
+            origin,
+            cursor,
+            targets,
+            index: 0,
+            path: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    /// Moves the cursor to the next (or previous, if `step` is negative) target.
+    pub fn cycle(&mut self, step: i32) {
+        if self.targets.is_empty() {
+            return;
+        }
+        self.index = (self.index as i32 + step).rem_euclid(self.targets.len() as i32) as usize;
+        self.cursor = self.targets[self.index].1;
+    }
+
+    /// Moves the cursor freely, keeping it on the map and within range.
+    pub fn move_cursor(&mut self, map: &Map, dx: i32, dy: i32) {
+        let (x, y) = (self.cursor.x + dx, self.cursor.y + dy);
+        if map.in_bounds(x, y) && self.in_range(x, y) {
+            self.cursor.x = x;
+            self.cursor.y = y;
+        }
+    }
+
+    pub fn in_range(&self, x: i32, y: i32) -> bool {
+        utils::distance2d_chebyshev((self.origin.x, self.origin.y), (x, y)) as i32 <= self.range
+    }
+
+    /// Whether the line of fire reaches the cursor.
+    pub fn reaches_cursor(&self) -> bool {
+        self.path.last() == Some(&(self.cursor.x, self.cursor.y))
+    }
+}
+
+/// Returns the tiles a projectile passes through going from `from` towards `to`.
+///
+/// The origin is not included. The path stops before any tile which blocks view, and at
+/// (including) the first tile for which `occupied` returns true.
+pub fn line_of_fire(map: &Map, from: (i32, i32), to: (i32, i32), occupied: impl Fn(i32, i32) -> bool) -> Vec<(i32, i32)> {
+    let mut path = Vec::new();
+    for (x, y) in utils::line(from, to).into_iter().skip(1) {
+        if !map.in_bounds(x, y) || map.blocks_view(x as usize, y as usize) {
+            break;
+        }
+        path.push((x, y));
+        if occupied(x, y) {
+            break;
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+
+    fn open_map() -> Map {
+        let mut map = Map::new(0, "test", 10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                map.define_tile(x, y, TileType::Floor);
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn line_of_fire_reaches_target_in_the_open() {
+        let map = open_map();
+        let path = line_of_fire(&map, (1, 1), (4, 1), |_, _| false);
+        assert_eq!(path, vec![(2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn line_of_fire_stops_at_walls_and_occupants() {
+        let mut map = open_map();
+        map.define_tile(3, 1, TileType::Wall);
+        assert_eq!(line_of_fire(&map, (1, 1), (5, 1), |_, _| false), vec![(2, 1)]);
+
+        let map = open_map();
+        assert_eq!(line_of_fire(&map, (1, 1), (5, 1), |x, _| x == 3), vec![(2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn targets_can_be_cycled() {
+        let origin = Position { x: 0, y: 0, map: 0 };
+        let targets = vec![
+            (Entity::from_raw(1), Position { x: 1, y: 0, map: 0 }),
+            (Entity::from_raw(2), Position { x: 2, y: 0, map: 0 }),
+        ];
+        let mut targeting = Targeting::new(TargetingPurpose::Cast(0), 5, origin, targets);
+        assert_eq!(targeting.cursor.x, 1);
+        targeting.cycle(1);
+        assert_eq!(targeting.cursor.x, 2);
+        targeting.cycle(1);
+        assert_eq!(targeting.cursor.x, 1);
+        targeting.cycle(-1);
+        assert_eq!(targeting.cursor.x, 2);
+    }
+}
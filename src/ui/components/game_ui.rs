@@ -3,21 +3,47 @@
 // use std::{collections::HashMap, time::Duration};
 use anyhow::Result;
 use bevy::log::{debug, debug_once};
-use ratatui::prelude::*;
+use ratatui::{buffer::Buffer, prelude::*, widgets::*};
 // use tokio::sync::mpsc::UnboundedSender;
 
 use crate::UIComponent;
-use crate::component::{Position, Render};
+use crate::component::{Position, Render, Stats};
+use crate::config::{LayoutConfig, UIConfig};
 use crate::game_event::GameEvent;
 use crate::map::{Map, camera::Camera};
 
+/// What the game UI shows while the player is picking a target.
+#[derive(Clone, Debug, Default)]
+pub struct TargetingOverlay {
+    pub action: &'static str,
+    pub cursor: (i32, i32),
+    pub path: Vec<(i32, i32)>,
+    /// Whether the line of fire is blocked before reaching the cursor.
+    pub blocked: bool,
+    pub description: String,
+}
+
 #[derive(Default)]
 pub struct GameUi {
     // command_tx: Option<UnboundedSender<GameEvent>>,
-    // config: UIConfig,
+    layout_config: LayoutConfig,
+    /// The map, log and sidebar areas for the terminal area they were laid out in.
+    layout: Option<(Rect, [Rect; 3])>,
     player_pos: Position,
-    map: Map,
+    map_name: String,
+    /// The revision of the map shown, see `Map::revision`.
+    map_revision: u64,
+    /// The part of the map in view, rendered when the map or anything shown on it changes. The
+    /// map itself stays in `CurrentGameData`, so it isn't copied.
+    map_view: Buffer,
     entities: Vec<(Position, Render)>,
+    player_name: String,
+    player_stats: Stats,
+    turn: u64,
+    log: Vec<String>,
+    statuses: Vec<(&'static str, u64)>,
+    next_level_xp: i32,
+    targeting: Option<TargetingOverlay>,
 }
 
 impl GameUi {
@@ -26,21 +52,129 @@ impl GameUi {
     }
 
     pub fn set_player_pos(&mut self, player_pos: Position) -> &mut Self {
-        debug!("set_player_pos: {:?}", player_pos);
         self.player_pos = player_pos;
         self
     }
 
-    pub fn set_map(&mut self, map: Map) -> &mut Self {
-        debug_once!("set_map: name: {} / width: {} / height: {} ", map.name, map.width, map.height);
-        self.map = map;
+    /// Renders the part of the map in view, with the entities and targeting overlay set before.
+    /// Until the game UI has been laid out there is nothing to render into, see `map_view_outdated`.
+    pub fn render_map(&mut self, map: &Map) -> &mut Self {
+        debug_once!(
+            "render_map: name: {} / width: {} / height: {} ",
+            map.name,
+            map.width,
+            map.height
+        );
+        self.map_name.clone_from(&map.name);
+        self.map_revision = map.revision();
+        let Some(area) = self.map_area() else {
+            return self;
+        };
+        let mut camera = Camera::new(self.player_pos, map, &self.entities);
+        if let Some(targeting) = &self.targeting {
+            let mut highlights: Vec<((i32, i32), Color)> = targeting.path.iter().map(|p| (*p, Color::Blue)).collect();
+            let cursor_color = if targeting.blocked { Color::Red } else { Color::Yellow };
+            highlights.push((targeting.cursor, cursor_color));
+            camera.set_highlights(highlights);
+        }
+        self.map_view = Buffer::empty(area);
+        camera.render(area, &mut self.map_view);
         self
     }
 
+    /// The revision of the map shown, see `Map::revision`.
+    pub fn map_revision(&self) -> u64 {
+        self.map_revision
+    }
+
+    /// Whether the map view was rendered for another screen area than the map now has, e.g. after
+    /// the terminal was resized.
+    pub fn map_view_outdated(&self) -> bool {
+        self.map_area().is_some_and(|area| area != self.map_view.area)
+    }
+
+    fn map_area(&self) -> Option<Rect> {
+        self.layout.map(|(_, [map_area, ..])| map_area)
+    }
+
     pub fn set_entities(&mut self, entities: Vec<(Position, Render)>) -> &mut Self {
         self.entities = entities;
         self
     }
+
+    pub fn set_player_stats(&mut self, name: String, stats: Stats) -> &mut Self {
+        self.player_name = name;
+        self.player_stats = stats;
+        self
+    }
+
+    pub fn set_statuses(&mut self, statuses: Vec<(&'static str, u64)>) -> &mut Self {
+        self.statuses = statuses;
+        self
+    }
+
+    pub fn statuses(&self) -> &[(&'static str, u64)] {
+        &self.statuses
+    }
+
+    pub fn set_next_level_xp(&mut self, xp: i32) -> &mut Self {
+        self.next_level_xp = xp;
+        self
+    }
+
+    pub fn set_targeting(&mut self, targeting: Option<TargetingOverlay>) -> &mut Self {
+        self.targeting = targeting;
+        self
+    }
+
+    pub fn has_targeting(&self) -> bool {
+        self.targeting.is_some()
+    }
+
+    pub fn set_turn(&mut self, turn: u64) -> &mut Self {
+        self.turn = turn;
+        self
+    }
+
+    pub fn set_log(&mut self, log: Vec<String>) -> &mut Self {
+        self.log = log;
+        self
+    }
+
+    fn sidebar(&self) -> Paragraph<'_> {
+        let stats = &self.player_stats;
+        let mut lines = vec![
+            Line::from(self.player_name.clone()).bold(),
+            Line::from(format!("HP: {}/{}", stats.hp.current, stats.hp.max)),
+            Line::from(format!("MP: {}/{}", stats.mp.current, stats.mp.max)),
+            Line::from(format!("Level: {}", stats.lv)),
+            Line::from(format!("XP: {}/{}", stats.xp, self.next_level_xp)),
+            Line::from(format!("DV: {}  PV: {}", stats.dv, stats.pv)),
+            Line::from(""),
+            Line::from(format!("Depth: {}", self.map_name)),
+            Line::from(format!("Turn: {}", self.turn)),
+        ];
+        if !self.statuses.is_empty() {
+            lines.push(Line::from(""));
+            for (name, duration) in self.statuses.iter() {
+                lines.push(Line::from(format!("{} ({})", name, duration)).yellow());
+            }
+        }
+        if let Some(targeting) = &self.targeting {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("{}: {}", targeting.action, targeting.description)).cyan());
+            if targeting.blocked {
+                lines.push(Line::from("(line of fire blocked)").red());
+            }
+        }
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded))
+    }
+
+    fn log(&self, height: u16) -> Paragraph<'_> {
+        let shown = self.log.len().saturating_sub(height.saturating_sub(2) as usize);
+        let lines: Vec<Line> = self.log.iter().skip(shown).map(|l| Line::from(l.as_str())).collect();
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded))
+    }
 }
 
 impl UIComponent for GameUi {
@@ -49,36 +183,116 @@ impl UIComponent for GameUi {
     //     Ok(())
     // }
 
-    // fn register_config_handler(&mut self, config: UIConfig) -> Result<()> {
-    //     self.config = config;
-    //     Ok(())
-    // }
+    fn register_config_handler(&mut self, config: UIConfig) -> Result<()> {
+        self.layout_config = config.layout;
+        self.layout = None;
+        Ok(())
+    }
 
-    fn update(&mut self, _action: GameEvent) -> Result<Option<GameEvent>> {
-        // match action {
-        //     Action::Tick => {}
-        //     _ => {}
-        // }
+    fn update(&mut self, action: GameEvent) -> Result<Option<GameEvent>> {
+        if let GameEvent::Resize(..) = action {
+            self.layout = None;
+        }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let horizontal_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(67), Constraint::Percentage(33)])
-            // .constraints([Constraint::Percentage(25), Constraint::Percentage(50), Constraint::Percentage(25)])
-            .split(area);
-
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(67), Constraint::Percentage(33)])
-            .split(horizontal_layout[0]);
-
-        let camera = Camera::new(self.player_pos, self.map.clone(), self.entities.clone());
-        // log::debug!("Drawing UIMap");
-
-        f.render_widget(camera, layout[0]);
-        // f.render_widget(Paragraph::new("helloworld I'M UIMap"), layout[1]);
+        let cfg = self.layout_config;
+        if area.width < cfg.min_width || area.height < cfg.min_height {
+            f.render_widget(Clear, area);
+            f.render_widget(too_small_warning(&cfg, area), area);
+            return Ok(());
+        }
+        let [map_area, log_area, sidebar_area] = match self.layout {
+            Some((laid_out, areas)) if laid_out == area => areas,
+            _ => {
+                let areas = game_layout(&cfg, area);
+                debug!("Laid out game UI for {}x{}: {:?}", area.width, area.height, areas);
+                self.layout = Some((area, areas));
+                areas
+            }
+        };
+
+        // An outdated view is left out, it's rendered again for the new area on the next update
+        if self.map_view.area == map_area {
+            let buf = f.buffer_mut();
+            for position in map_area.positions() {
+                buf[position] = self.map_view[position].clone();
+            }
+        }
+        f.render_widget(self.log(log_area.height), log_area);
+        f.render_widget(self.sidebar(), sidebar_area);
         Ok(())
     }
 }
+
+/// Splits the screen into the map, log and sidebar areas.
+fn game_layout(cfg: &LayoutConfig, area: Rect) -> [Rect; 3] {
+    let map_width = cfg.map_width.min(100);
+    let map_height = cfg.map_height.min(100);
+    let [main, sidebar] =
+        Layout::horizontal([Constraint::Percentage(map_width), Constraint::Percentage(100 - map_width)]).areas(area);
+    let [map, log] =
+        Layout::vertical([Constraint::Percentage(map_height), Constraint::Percentage(100 - map_height)]).areas(main);
+    [map, log, sidebar]
+}
+
+/// Shown instead of the game while the terminal is too small for it.
+fn too_small_warning(cfg: &LayoutConfig, area: Rect) -> Paragraph<'static> {
+    let lines = vec![
+        Line::from("Terminal too small"),
+        Line::from(format!("need at least {}x{}", cfg.min_width, cfg.min_height)),
+        Line::from(format!("have {}x{}", area.width, area.height)),
+    ];
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let mut padded = vec![Line::default(); top as usize];
+    padded.extend(lines);
+    Paragraph::new(padded)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileType;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn map_view_is_rendered_for_the_laid_out_area() {
+        let mut map = Map::new(1, "test", 10, 5);
+        map.define_tile(2, 1, TileType::Floor);
+        map.set_revealed(2, 1, true);
+        map.set_visible(2, 1, true);
+        let mut terminal = Terminal::new(TestBackend::new(100, 50)).unwrap();
+        let mut game_ui = GameUi::new();
+        let mut draw = |game_ui: &mut GameUi| {
+            terminal.draw(|f| game_ui.draw(f, f.area()).unwrap()).unwrap();
+            terminal.backend().buffer()[(2, 1)].symbol().to_string()
+        };
+
+        game_ui.render_map(&map);
+        assert!(!game_ui.map_view_outdated());
+        assert_eq!(draw(&mut game_ui), " ");
+        assert!(game_ui.map_view_outdated());
+
+        game_ui.render_map(&map);
+        assert!(!game_ui.map_view_outdated());
+        assert_eq!(draw(&mut game_ui), map.glyph(2, 1).to_string());
+        assert_eq!(game_ui.map_revision(), map.revision());
+    }
+
+    #[test]
+    fn layout_uses_the_configured_proportions() {
+        let cfg = LayoutConfig {
+            map_width: 75,
+            map_height: 80,
+            ..Default::default()
+        };
+        let [map, log, sidebar] = game_layout(&cfg, Rect::new(0, 0, 100, 50));
+        assert_eq!((map.width, map.height), (75, 40));
+        assert_eq!((log.y, log.height), (40, 10));
+        assert_eq!((sidebar.x, sidebar.width), (75, 25));
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use bevy_ecs::entity::Entity;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::UIComponent;
+use crate::game_event::GameEvent;
+use crate::item::ItemAction;
+use crate::ui::centered_rect;
+
+/// Keys shown for the context actions. They should match the `ItemDetail` keybindings.
+const ACTION_KEYS: [(ItemAction, &str); 5] = [
+    (ItemAction::Use, "a"),
+    (ItemAction::Throw, "t"),
+    (ItemAction::Equip, "w"),
+    (ItemAction::Remove, "T"),
+    (ItemAction::Drop, "d"),
+];
+
+/// Popup with the details of a single item, and the things which can be done with it.
+pub struct ItemDetail {
+    item: Entity,
+    title: String,
+    lines: Vec<String>,
+    actions: Vec<ItemAction>,
+}
+
+impl ItemDetail {
+    pub fn new<T: ToString>(item: Entity, title: T, lines: Vec<String>, actions: Vec<ItemAction>) -> Self {
+        Self {
+            item,
+            title: title.to_string(),
+            lines,
+            actions,
+        }
+    }
+
+    fn action_hints(&self) -> String {
+        let mut hints: Vec<String> = ACTION_KEYS
+            .iter()
+            .filter(|(action, _)| self.actions.contains(action))
+            .map(|(action, key)| format!("{}: {}", key, action.label()))
+            .collect();
+        hints.push("esc: back".to_string());
+        hints.join("   ")
+    }
+}
+
+impl UIComponent for ItemDetail {
+    fn update(&mut self, action: GameEvent) -> Result<Option<GameEvent>> {
+        if let GameEvent::ItemContextAction(action) = action
+            && self.actions.contains(&action)
+        {
+            return Ok(Some(GameEvent::ChooseItemAction { item: self.item, action }));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, _area: Rect) -> Result<()> {
+        let area = centered_rect(f.area(), 50, 40);
+        let block = Block::default()
+            .title(self.title.clone())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let lines: Vec<Line> = self.lines.iter().map(|l| Line::from(l.clone())).collect();
+        f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), layout[0]);
+        f.render_widget(Paragraph::new(self.action_hints()).dark_gray(), layout[1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use bevy_ecs::entity::Entity;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::UIComponent;
+use crate::game_event::GameEvent;
+use crate::ui::centered_rect;
+
+/// Letters used to pick entries in a lettered list, in order.
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// A lettered list of items, used for the inventory and the equipment screens. Picking an entry,
+/// by its letter or by moving the selection, shows the details of its item.
+/// Entries without an item (e.g. empty equipment slots) can't be picked.
+#[derive(Default)]
+pub struct ItemList {
+    title: String,
+    entries: Vec<(String, Option<Entity>)>,
+    state: ListState,
+}
+
+impl ItemList {
+    pub fn new<T: ToString>(title: T) -> Self {
+        let mut list = Self {
+            title: title.to_string(),
+            ..Default::default()
+        };
+        list.state.select(Some(0));
+        list
+    }
+
+    pub fn add_entry<T: ToString>(&mut self, text: T, item: Option<Entity>) -> &mut Self {
+        self.entries.push((text.to_string(), item));
+        self
+    }
+
+    fn letter_index(letter: char) -> Option<usize> {
+        LETTERS.chars().position(|c| c == letter)
+    }
+
+    fn show(&self, index: usize) -> Option<GameEvent> {
+        self.entries
+            .get(index)
+            .and_then(|(_, item)| *item)
+            .map(GameEvent::ShowItemDetail)
+    }
+}
+
+impl UIComponent for ItemList {
+    fn update(&mut self, action: GameEvent) -> Result<Option<GameEvent>> {
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+        let index = self.state.selected().unwrap_or(0);
+        match action {
+            GameEvent::NextMenuItem => self.state.select(Some((index + 1) % self.entries.len())),
+            GameEvent::PrevMenuItem => self.state.select(Some((index + self.entries.len() - 1) % self.entries.len())),
+            GameEvent::SelectMenuItem => return Ok(self.show(index)),
+            GameEvent::SelectLetter(letter) => {
+                if let Some(index) = Self::letter_index(letter).filter(|i| *i < self.entries.len()) {
+                    self.state.select(Some(index));
+                    return Ok(self.show(index));
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, _area: Rect) -> Result<()> {
+        let area = centered_rect(f.area(), 50, 60);
+        let block = Block::default()
+            .title(self.title.clone())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let items: Vec<ListItem> = if self.entries.is_empty() {
+            vec![ListItem::new("You aren't carrying anything.").dark_gray()]
+        } else {
+            self.entries
+                .iter()
+                .zip(LETTERS.chars())
+                .map(|((text, item), letter)| {
+                    let line = format!("{} - {}", letter, text);
+                    if item.is_some() {
+                        ListItem::new(line)
+                    } else {
+                        ListItem::new(line).dark_gray()
+                    }
+                })
+                .collect()
+        };
+        let list = List::new(items)
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, layout[0], &mut self.state);
+
+        f.render_widget(Paragraph::new("letter/enter: details   esc: back").dark_gray(), layout[1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::UIComponent;
+
+/// Lists the keys which can follow a prefix key, in the bottom right corner of the screen.
+#[derive(Default)]
+pub struct KeyHints {
+    prefix: String,
+    hints: Vec<(String, String)>,
+}
+
+impl KeyHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_hints(&mut self, prefix: String, hints: Vec<(String, String)>) -> &mut Self {
+        self.prefix = prefix;
+        self.hints = hints;
+        self
+    }
+}
+
+impl UIComponent for KeyHints {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let key_width = self.hints.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = self
+            .hints
+            .iter()
+            .map(|(keys, event)| {
+                Line::from(vec![
+                    Span::styled(format!("{keys:<key_width$}"), Style::default().fg(Color::Yellow)),
+                    Span::raw(format!("  {event}")),
+                ])
+            })
+            .collect();
+        let width = lines
+            .iter()
+            .map(|l| l.width() as u16)
+            .max()
+            .unwrap_or(0)
+            .max(self.prefix.len() as u16)
+            + 4;
+        let height = lines.len() as u16 + 2;
+        let popup = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.bottom().saturating_sub(height),
+            width: width.min(area.width),
+            height: height.min(area.height),
+        };
+        let block = Block::default()
+            .title(self.prefix.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::UIComponent;
+use crate::config::{InputContext, KeyBindings, key_sequence_to_string, parse_key_sequence};
+use crate::game_event::GameEvent;
+use crate::ui::centered_rect;
+
+/// A binding being changed, waiting for the new key.
+struct Capture {
+    row: usize,
+    /// A key which clashes with other bindings, pressed once already. Pressing it again replaces them.
+    replacing: Option<Vec<KeyEvent>>,
+}
+
+/// Lists the key bindings of every input context and lets the player change them. Changes are
+/// kept here until they're saved.
+#[derive(Default)]
+pub struct KeyBindingEditor {
+    bindings: KeyBindings,
+    rows: Vec<(InputContext, Vec<KeyEvent>, GameEvent)>,
+    state: ListState,
+    capture: Option<Capture>,
+    message: String,
+    changed: bool,
+}
+
+impl KeyBindingEditor {
+    pub fn new(bindings: KeyBindings) -> Self {
+        let mut editor = Self {
+            bindings,
+            ..Default::default()
+        };
+        editor.update_rows();
+        editor.state.select(Some(0));
+        editor
+    }
+
+    pub fn bindings(&self) -> &KeyBindings {
+        &self.bindings
+    }
+
+    /// Shows the outcome of saving the bindings.
+    pub fn saved(&mut self, result: Result<String>) {
+        match result {
+            Ok(path) => {
+                self.changed = false;
+                self.message = format!("Saved to {path}.");
+            }
+            Err(e) => self.message = format!("Saving failed: {e}"),
+        }
+    }
+
+    fn update_rows(&mut self) {
+        self.rows = self
+            .bindings
+            .iter()
+            .flat_map(|(context, bindings)| {
+                bindings
+                    .iter()
+                    .map(|(keys, event)| (context.clone(), keys.clone(), event.clone()))
+            })
+            .collect();
+        self.rows.sort_by_cached_key(|(context, keys, event)| {
+            (context.to_string(), format!("{event:?}"), key_sequence_to_string(keys))
+        });
+    }
+
+    fn select_binding(&mut self, context: &InputContext, keys: &[KeyEvent]) {
+        let index = self.rows.iter().position(|(c, k, _)| c == context && k == keys);
+        self.state.select(index.or(Some(0)));
+    }
+
+    fn captured(&mut self, key: &str) -> Option<GameEvent> {
+        let capture = self.capture.take()?;
+        let (context, old_keys, event) = self.rows.get(capture.row)?.clone();
+        let Ok(keys) = parse_key_sequence(key) else {
+            self.message = format!("Can't bind <{key}>.");
+            return None;
+        };
+        if keys.first().is_some_and(|k| k.code == KeyCode::Esc) {
+            self.message = "Nothing changed.".to_string();
+            return None;
+        }
+        let shown = key_sequence_to_string(&keys);
+
+        let clashes: Vec<_> = conflicts(&self.bindings, &context, &keys)
+            .into_iter()
+            .filter(|(k, _)| *k != old_keys)
+            .collect();
+        if !clashes.is_empty() && capture.replacing.as_ref() != Some(&keys) {
+            let bound: Vec<String> = clashes
+                .iter()
+                .map(|(k, e)| format!("{} ({e:?})", key_sequence_to_string(k)))
+                .collect();
+            self.message = format!(
+                "{shown} clashes with {} in {context}. Press it again to replace, or another key.",
+                bound.join(", ")
+            );
+            self.capture = Some(Capture {
+                row: capture.row,
+                replacing: Some(keys),
+            });
+            return Some(GameEvent::StartKeyCapture);
+        }
+
+        let bindings = self.bindings.entry(context.clone()).or_default();
+        for (k, _) in clashes {
+            bindings.remove(&k);
+        }
+        bindings.remove(&old_keys);
+        bindings.insert(keys.clone(), event.clone());
+        self.changed = true;
+        self.message = format!("Bound {shown} to {event:?} in {context}.");
+        self.update_rows();
+        self.select_binding(&context, &keys);
+        None
+    }
+}
+
+/// The bindings in a context which clash with binding the given keys: the same keys, and
+/// sequences starting with them or which they start with.
+pub fn conflicts(bindings: &KeyBindings, context: &InputContext, keys: &[KeyEvent]) -> Vec<(Vec<KeyEvent>, GameEvent)> {
+    let Some(bindings) = bindings.get(context) else {
+        return Vec::new();
+    };
+    let mut clashes: Vec<_> = bindings
+        .iter()
+        .filter(|(k, _)| k.starts_with(keys) || keys.starts_with(k))
+        .map(|(k, e)| (k.clone(), e.clone()))
+        .collect();
+    clashes.sort_by_cached_key(|(k, _)| key_sequence_to_string(k));
+    clashes
+}
+
+impl UIComponent for KeyBindingEditor {
+    fn update(&mut self, action: GameEvent) -> Result<Option<GameEvent>> {
+        if let GameEvent::CapturedKey(key) = action {
+            return Ok(self.captured(&key));
+        }
+        if self.capture.is_some() || self.rows.is_empty() {
+            return Ok(None);
+        }
+        let index = self.state.selected().unwrap_or(0);
+        match action {
+            GameEvent::NextMenuItem => self.state.select(Some((index + 1) % self.rows.len())),
+            GameEvent::PrevMenuItem => self.state.select(Some((index + self.rows.len() - 1) % self.rows.len())),
+            GameEvent::SelectMenuItem => {
+                let (context, keys, event) = &self.rows[index];
+                self.message = format!(
+                    "Press the new key for {event:?} in {context} (now {}), esc to cancel.",
+                    key_sequence_to_string(keys)
+                );
+                self.capture = Some(Capture {
+                    row: index,
+                    replacing: None,
+                });
+                return Ok(Some(GameEvent::StartKeyCapture));
+            }
+            GameEvent::UnbindKey => {
+                let (context, keys, event) = self.rows[index].clone();
+                if let Some(bindings) = self.bindings.get_mut(&context) {
+                    bindings.remove(&keys);
+                }
+                self.changed = true;
+                self.message = format!("Unbound {} ({event:?}) in {context}.", key_sequence_to_string(&keys));
+                self.update_rows();
+                self.state.select(Some(index.min(self.rows.len().saturating_sub(1))));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, _area: Rect) -> Result<()> {
+        let area = centered_rect(f.area(), 70, 80);
+        let title = if self.changed {
+            "Key Bindings (unsaved)"
+        } else {
+            "Key Bindings"
+        };
+        let block = Block::default()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1), Constraint::Length(1)])
+            .split(inner);
+
+        let items: Vec<String> = self
+            .rows
+            .iter()
+            .map(|(context, keys, event)| {
+                format!("{:<16}{:<22}{:?}", context.to_string(), key_sequence_to_string(keys), event)
+            })
+            .collect();
+        let list = List::new(items)
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, layout[0], &mut self.state);
+
+        f.render_widget(Paragraph::new(self.message.as_str()).yellow(), layout[1]);
+        f.render_widget(
+            Paragraph::new("enter: rebind   d: unbind   s: save   esc: back").dark_gray(),
+            layout[2],
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::KeyBindingChanges;
+
+    fn keys(raw: &str) -> Vec<KeyEvent> {
+        parse_key_sequence(raw).unwrap()
+    }
+
+    fn editor() -> KeyBindingEditor {
+        let mut bindings = KeyBindings::default();
+        bindings.insert(
+            InputContext::State(crate::GameState::InGame),
+            [
+                (keys("<j>"), GameEvent::PlayerWait),
+                (keys("<q>"), GameEvent::Quit),
+                (keys("<shift-z><shift-z>"), GameEvent::ShowMainMenu),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        KeyBindingEditor::new(bindings)
+    }
+
+    #[test]
+    fn clashes_include_sequences_sharing_a_prefix() {
+        let editor = editor();
+        let context = InputContext::State(crate::GameState::InGame);
+        assert_eq!(
+            conflicts(&editor.bindings, &context, &keys("<q>")),
+            vec![(keys("<q>"), GameEvent::Quit)]
+        );
+        assert_eq!(conflicts(&editor.bindings, &context, &keys("<shift-z>")).len(), 1);
+        assert!(conflicts(&editor.bindings, &context, &keys("<x>")).is_empty());
+    }
+
+    #[test]
+    fn rebinding_to_a_bound_key_needs_confirming() {
+        let mut editor = editor();
+        let context = InputContext::State(crate::GameState::InGame);
+        // Rows are sorted by event, so PlayerWait comes first
+        assert_eq!(editor.rows[0].2, GameEvent::PlayerWait);
+        assert_eq!(
+            editor.update(GameEvent::SelectMenuItem).unwrap(),
+            Some(GameEvent::StartKeyCapture)
+        );
+        assert_eq!(
+            editor.update(GameEvent::CapturedKey("q".to_string())).unwrap(),
+            Some(GameEvent::StartKeyCapture)
+        );
+        assert!(!editor.changed);
+        assert_eq!(editor.update(GameEvent::CapturedKey("q".to_string())).unwrap(), None);
+        let bindings = editor.bindings().get(&context).unwrap();
+        assert_eq!(bindings.get(&keys("<q>")), Some(&GameEvent::PlayerWait));
+        assert!(!bindings.contains_key(&keys("<j>")));
+        assert_eq!(bindings.len(), 2);
+    }
+
+    #[test]
+    fn only_changed_bindings_are_saved() {
+        let mut editor = editor();
+        let defaults = editor.bindings().clone();
+        let context = InputContext::State(crate::GameState::InGame);
+        // Rebind PlayerWait from <j> to <x>
+        editor.update(GameEvent::SelectMenuItem).unwrap();
+        editor.update(GameEvent::CapturedKey("x".to_string())).unwrap();
+
+        let changes = KeyBindingChanges::between(&defaults, editor.bindings());
+        let saved = serde_json::to_string(&changes).unwrap();
+        assert_eq!(saved, r#"{"InGame":{"<j>":null,"<x>":"PlayerWait"}}"#);
+
+        // Loading applies them key by key, keeping the other defaults and any added since
+        let read: KeyBindingChanges = serde_json::from_str(&saved).unwrap();
+        let mut bindings = defaults.clone();
+        bindings.get_mut(&context).unwrap().insert(keys("<n>"), GameEvent::PlayerRest);
+        read.apply(&mut bindings);
+        let bindings = &bindings[&context];
+        assert_eq!(bindings.get(&keys("<x>")), Some(&GameEvent::PlayerWait));
+        assert_eq!(bindings.get(&keys("<q>")), Some(&GameEvent::Quit));
+        assert_eq!(bindings.get(&keys("<n>")), Some(&GameEvent::PlayerRest));
+        assert!(!bindings.contains_key(&keys("<j>")));
+    }
+}
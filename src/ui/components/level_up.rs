@@ -0,0 +1,97 @@
+use anyhow::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::UIComponent;
+use crate::component::{Skill, Skills, Stats};
+use crate::game_event::GameEvent;
+use crate::ui::centered_rect;
+
+const SKILLS: [(Skill, &str); 3] = [(Skill::Melee, "Melee"), (Skill::Defense, "Defense"), (Skill::Magic, "Magic")];
+
+/// Shows the player's level and lets them spend skill points.
+#[derive(Default)]
+pub struct LevelUpScreen {
+    stats: Stats,
+    skills: Skills,
+    next_level_xp: i32,
+    state: ListState,
+}
+
+impl LevelUpScreen {
+    pub fn new() -> Self {
+        let mut screen = Self::default();
+        screen.state.select(Some(0));
+        screen
+    }
+
+    pub fn set_player(&mut self, stats: Stats, skills: Skills, next_level_xp: i32) -> &mut Self {
+        self.stats = stats;
+        self.skills = skills;
+        self.next_level_xp = next_level_xp;
+        self
+    }
+
+    fn selected_skill(&self) -> Skill {
+        SKILLS[self.state.selected().unwrap_or(0)].0
+    }
+}
+
+impl UIComponent for LevelUpScreen {
+    fn update(&mut self, action: GameEvent) -> Result<Option<GameEvent>> {
+        match action {
+            GameEvent::NextMenuItem => {
+                let index = self.state.selected().unwrap_or(0);
+                self.state.select(Some((index + 1) % SKILLS.len()));
+            }
+            GameEvent::PrevMenuItem => {
+                let index = self.state.selected().unwrap_or(0);
+                self.state.select(Some((index + SKILLS.len() - 1) % SKILLS.len()));
+            }
+            GameEvent::SelectMenuItem if self.skills.unspent > 0 => {
+                return Ok(Some(GameEvent::AllocateSkillPoint(self.selected_skill())));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, _area: Rect) -> Result<()> {
+        let area = centered_rect(f.area(), 40, 40);
+        let block = Block::default()
+            .title(format!("Level {}", self.stats.lv))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Min(3), Constraint::Length(1)])
+            .split(inner);
+
+        let summary = vec![
+            Line::from(format!("Experience: {} / {}", self.stats.xp, self.next_level_xp)),
+            Line::from(format!("HP: {}  MP: {}", self.stats.hp.max, self.stats.mp.max)),
+            Line::from(""),
+            Line::from(format!("Unspent skill points: {}", self.skills.unspent)).bold(),
+        ];
+        f.render_widget(Paragraph::new(summary), layout[0]);
+
+        let items: Vec<String> = SKILLS
+            .iter()
+            .map(|(skill, name)| format!("{:<10}{:>3}", name, self.skills.get(*skill)))
+            .collect();
+        let list = List::new(items)
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, layout[1], &mut self.state);
+
+        f.render_widget(Paragraph::new("enter: spend point   esc: back").dark_gray(), layout[2]);
+
+        Ok(())
+    }
+}
@@ -37,6 +37,14 @@ impl Menu {
         self
     }
 
+    pub fn select(&mut self, index: usize) -> &mut Self {
+        if index < self.items.len() {
+            self.index = index;
+            self.state.select(Some(self.index));
+        }
+        self
+    }
+
     fn next_item(&mut self) -> Result<()> {
         self.index = (self.index + 1) % self.items.len();
         self.state.select(Some(self.index));
@@ -1,10 +1,20 @@
 mod game_ui;
 mod home;
+mod item_detail;
+mod item_list;
+mod key_hints;
+mod keybinding_editor;
+mod level_up;
 mod menu;
 
 use crate::UIComponent;
 pub use game_ui::*;
 pub use home::*;
+pub use item_detail::*;
+pub use item_list::*;
+pub use key_hints::*;
+pub use keybinding_editor::*;
+pub use level_up::*;
 pub use menu::*;
 
 use bevy_ecs::resource::Resource;
@@ -13,6 +23,8 @@ use std::collections::HashMap;
 pub struct UIComponentData {
     pub component: Box<dyn UIComponent>,
     pub visible: bool,
+    /// Components on higher layers are drawn on top of those on lower layers.
+    pub layer: i32,
 }
 
 impl Default for UIComponentData {
@@ -20,6 +32,7 @@ impl Default for UIComponentData {
         Self {
             component: Box::new(Home::new()) as Box<dyn UIComponent>,
             visible: false,
+            layer: 0,
         }
     }
 }
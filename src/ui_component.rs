@@ -1,6 +1,7 @@
 use anyhow::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::layout::{Rect, Size};
+use std::any::Any;
 use tokio::sync::mpsc::UnboundedSender;
 
 // pub mod components;
@@ -12,7 +13,7 @@ use ratatui::Frame;
 /// `UIComponent` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
 /// update state, and be rendered on the screen.
-pub trait UIComponent: Send + Sync {
+pub trait UIComponent: Any + Send + Sync {
     /// Register an action handler that can send actions for processing if necessary.
     ///
     /// # Arguments
@@ -54,15 +55,15 @@ pub trait UIComponent: Send + Sync {
         Ok(())
     }
 
-    /// Handle incoming events and produce actions if necessary.
-    ///
-    /// # Arguments
-    ///
-    /// * `event` - An optional event to be processed.
-    ///
-    /// # Returns
-    ///
-    /// * `Result<Option<Action>>` - An action to be processed or none.
+    // Handle incoming events and produce actions if necessary.
+    //
+    // # Arguments
+    //
+    // * `event` - An optional event to be processed.
+    //
+    // # Returns
+    //
+    // * `Result<Option<Action>>` - An action to be processed or none.
     // fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
     //     let r = match event {
     //         Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
@@ -126,3 +127,10 @@ pub trait UIComponent: Send + Sync {
     /// * `Result<()>` - An Ok result or an error.
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()>;
 }
+
+impl dyn UIComponent {
+    /// Returns the component as its concrete type, if it is of type `T`.
+    pub fn downcast_mut<T: UIComponent>(&mut self) -> Option<&mut T> {
+        (self as &mut dyn Any).downcast_mut::<T>()
+    }
+}
@@ -35,7 +35,6 @@ pub fn distance2d_manhattan(start: (i32, i32), end: (i32, i32)) -> f32 {
     dx + dy
 }
 
-#[allow(dead_code)]
 /// Calculates a Chebyshev distance between two points
 /// See: http://theory.stanford.edu/~amitp/GameProgramming/Heuristics.html
 pub fn distance2d_chebyshev(start: (i32, i32), end: (i32, i32)) -> f32 {
@@ -43,3 +42,50 @@ pub fn distance2d_chebyshev(start: (i32, i32), end: (i32, i32)) -> f32 {
     let dy = (max(start.1, end.1) - min(start.1, end.1)) as f32;
     if dx > dy { (dx - dy) + 1.0 * dy } else { (dy - dx) + 1.0 * dx }
 }
+
+/// Returns all points on a line from `start` to `end` (inclusive), using Bresenham's algorithm.
+pub fn line(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut points = Vec::new();
+    let (mut x, mut y) = start;
+    let dx = (end.0 - start.0).abs();
+    let dy = -(end.1 - start.1).abs();
+    let sx = if start.0 < end.0 { 1 } else { -1 };
+    let sy = if start.1 < end.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        points.push((x, y));
+        if x == end.0 && y == end.1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_includes_both_endpoints() {
+        let points = line((0, 0), (5, 2));
+        assert_eq!(points.first(), Some(&(0, 0)));
+        assert_eq!(points.last(), Some(&(5, 2)));
+        assert_eq!(points.len(), 6);
+    }
+
+    #[test]
+    fn line_to_self_is_single_point() {
+        assert_eq!(line((3, 3), (3, 3)), vec![(3, 3)]);
+    }
+}
@@ -1,16 +1,25 @@
 use crate::{
-    CFG, GameEvent, GameState,
+    CFG, GameState, MAIN_MENU_NAME, UIComponents,
     component::*,
-    game::CurrentGameData,
+    game::{CurrentGameData, GameTime},
+    gamelog::GameLog,
+    item::{self, KnownItems},
     map::{Map, Maps, generate_builder_chain},
-    player, utils,
+    player,
+    rng::{self, Seed},
+    ui::components::Menu,
+    utils,
 };
 use bevy::log::*;
 use bevy::prelude::*;
 
-fn generate_maps(first: usize, last: usize) -> (Maps, Position) {
+/// Items to spawn once the world exists: where, which item and how many.
+type SpawnList = Vec<(Position, String, i32)>;
+
+fn generate_maps(first: usize, last: usize) -> (Maps, Position, SpawnList) {
     let mut maps = Maps::new();
     let mut dungeon_entry = Position::default();
+    let mut spawn_list = SpawnList::new();
 
     // Add an empty map at index 0
     maps.map.push(Map::new(0, "Zero", 1, 1));
@@ -27,10 +36,11 @@ fn generate_maps(first: usize, last: usize) -> (Maps, Position) {
             dungeon_entry = p;
         }
 
+        spawn_list.append(&mut builder.get_spawn_list());
         maps.map.push(builder.get_map());
     }
 
-    (maps, dungeon_entry)
+    (maps, dungeon_entry, spawn_list)
 }
 
 fn temp_spawn_npc_entities(world: &mut World) {
@@ -50,9 +60,22 @@ fn temp_spawn_npc_entities(world: &mut World) {
             fg: ratatui::style::Color::Red,
             bg: ratatui::style::Color::Black,
             order: 1,
-            always: true,
+            always: false,
         });
         entity.insert(MovementType::Random);
+        entity.insert(Stats {
+            hp: Pool::new(5),
+            lv: rng::range(1, 3),
+            ..Default::default()
+        });
+        entity.insert(Attributes::default());
+        entity.insert(Regeneration {
+            hp: Regen {
+                frequency: 10,
+                amount: 1,
+            },
+            mp: Regen::default(),
+        });
     }
 }
 
@@ -71,10 +94,18 @@ pub fn generate_world(world: &mut World) {
     world.clear_entities();
 
     info!("Generating maps");
-    let (maps, dungeon_entry) = generate_maps(first_map, last_map);
+    let (maps, dungeon_entry, spawn_list) = generate_maps(first_map, last_map);
 
     info!("Spawning player entity");
     let player = player::spawn(world, dungeon_entry);
+    let known_items = KnownItems::new(world.resource::<Seed>().0, &CFG.lock().unwrap().config.items);
+    world.insert_resource(known_items);
+    item::spawn_player_kit(world, player);
+
+    info!("Spawning {} items", spawn_list.len());
+    for (pos, name, count) in spawn_list {
+        item::spawn_on_floor(world, &name, count, pos);
+    }
     utils::print_entity_components(world, player);
 
     let gamedata = CurrentGameData {
@@ -86,6 +117,8 @@ pub fn generate_world(world: &mut World) {
 
     info!("Inserting resources");
     world.insert_resource(gamedata);
+    world.insert_resource(GameTime::default());
+    world.insert_resource(GameLog::default());
 
     temp_spawn_npc_entities(world);
 
@@ -93,5 +126,13 @@ pub fn generate_world(world: &mut World) {
     let mut game_state = world.resource_mut::<NextState<GameState>>();
     game_state.set(GameState::Menu); // will use default MenuState which is MainMenu
 
-    world.send_event(GameEvent::NextMenuItem);
+    // Move the main menu selection on to "New Game"
+    if let Some(menu) = world
+        .resource_mut::<UIComponents>()
+        .comps
+        .get_mut(MAIN_MENU_NAME)
+        .and_then(|c| c.component.downcast_mut::<Menu>())
+    {
+        menu.select(1);
+    }
 }